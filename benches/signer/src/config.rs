@@ -0,0 +1,50 @@
+use std::fs;
+
+use cb_common::types::Chain;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Config {
+    pub chain: Chain,
+    pub benchmark: BenchmarkConfig,
+    pub bench: Vec<BenchConfig>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BenchmarkConfig {
+    /// Signs run before the measured ones, reported apart
+    pub warmup_signs: u64,
+    /// Signs measured in the steady state
+    pub n_signs: u64,
+    /// Same as the signer `verify_signatures`
+    #[serde(default)]
+    pub verify_signatures: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BenchConfig {
+    pub id: String,
+    pub kind: SignKind,
+    /// Number of signs in flight at the same time
+    pub concurrency: u64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignKind {
+    Consensus,
+    ProxyBls,
+    ProxyEcdsa,
+}
+
+pub fn load_static_config() -> Config {
+    let path =
+        std::env::args().nth(1).expect("missing config path. Add config eg. `bench-config.toml'");
+    let config_file = fs::read_to_string(&path)
+        .unwrap_or_else(|_| panic!("Unable to find config file: '{}'", path));
+    let config: Config = toml::from_str(&config_file).expect("failed to parse toml");
+
+    assert!(config.bench.iter().all(|bench| bench.concurrency > 0), "concurrency must be > 0");
+
+    config
+}
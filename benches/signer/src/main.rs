@@ -0,0 +1,185 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use cb_common::{
+    signer::{BlsPublicKey, ConsensusSigner, EcdsaPublicKey},
+    types::{Chain, ModuleId},
+};
+use cb_signer::manager::SigningManager;
+use comfy_table::Table;
+use config::{load_static_config, SignKind};
+use histogram::Histogram;
+use tokio::task::JoinSet;
+
+mod config;
+
+const BENCH_MODULE_ID: &str = "BENCH_MODULE";
+
+#[tokio::main]
+async fn main() {
+    let config = load_static_config();
+
+    let mut bench_results = Vec::with_capacity(config.bench.len() * 2);
+
+    for bench in config.bench {
+        print!("Benching {}...", bench.id);
+        let total_start = Instant::now();
+
+        let target = Arc::new(
+            SignTarget::new(config.chain, bench.kind, config.benchmark.verify_signatures).await,
+        );
+
+        let warmup = run_phase(&target, bench.concurrency, config.benchmark.warmup_signs).await;
+        let steady = run_phase(&target, bench.concurrency, config.benchmark.n_signs).await;
+
+        println!("took {:?}", total_start.elapsed());
+
+        bench_results.push(BenchResults { id: bench.id.clone(), phase: "warmup", stats: warmup });
+        bench_results.push(BenchResults { id: bench.id, phase: "steady", stats: steady });
+    }
+
+    let mut table = Table::new();
+    table.set_header(vec!["ID", "Phase", "Signs/s", "p50", "p90", "p95", "p99"]);
+
+    for result in bench_results {
+        let stats = result.stats;
+        table.add_row(vec![
+            result.id,
+            result.phase.to_string(),
+            format!("{:.0}", stats.throughput()),
+            stats.percentile(50.),
+            stats.percentile(90.),
+            stats.percentile(95.),
+            stats.percentile(99.),
+        ]);
+    }
+
+    println!();
+
+    println!("Bench results");
+    println!("Warmup signs are not included in the steady state results");
+    println!("{table}");
+}
+
+/// Key that is signed with in a bench
+enum BenchKey {
+    Consensus(BlsPublicKey),
+    ProxyBls(BlsPublicKey),
+    ProxyEcdsa(EcdsaPublicKey),
+}
+
+/// Signing manager with a single key of the benched kind
+struct SignTarget {
+    manager: SigningManager,
+    key: BenchKey,
+}
+
+impl SignTarget {
+    async fn new(chain: Chain, kind: SignKind, verify_signatures: bool) -> Self {
+        let mut manager = SigningManager::new(chain, None).expect("failed to create manager");
+        manager.set_verify_signatures(verify_signatures);
+
+        let consensus = ConsensusSigner::new_random();
+        let consensus_pk = consensus.pubkey();
+        manager.add_consensus_signer(consensus);
+
+        let module_id = ModuleId(BENCH_MODULE_ID.to_string());
+        let key = match kind {
+            SignKind::Consensus => BenchKey::Consensus(consensus_pk),
+            SignKind::ProxyBls => {
+                let delegation = manager
+                    .create_proxy_bls(module_id, consensus_pk)
+                    .await
+                    .expect("failed to create proxy");
+                BenchKey::ProxyBls(delegation.message.proxy)
+            }
+            SignKind::ProxyEcdsa => {
+                let delegation = manager
+                    .create_proxy_ecdsa(module_id, consensus_pk)
+                    .await
+                    .expect("failed to create proxy");
+                BenchKey::ProxyEcdsa(delegation.message.proxy)
+            }
+        };
+
+        Self { manager, key }
+    }
+
+    async fn sign(&self, object_root: &[u8; 32]) {
+        let res = match &self.key {
+            BenchKey::Consensus(pubkey) => {
+                self.manager.sign_consensus(pubkey, object_root).await.map(|_| ())
+            }
+            BenchKey::ProxyBls(pubkey) => {
+                self.manager.sign_proxy_bls(pubkey, object_root).await.map(|_| ())
+            }
+            BenchKey::ProxyEcdsa(pubkey) => {
+                self.manager.sign_proxy_ecdsa(pubkey, object_root).await.map(|_| ())
+            }
+        };
+
+        res.expect("failed to sign");
+    }
+}
+
+/// Runs `n_signs` signs split across `concurrency` tasks
+async fn run_phase(target: &Arc<SignTarget>, concurrency: u64, n_signs: u64) -> PhaseStats {
+    let start = Instant::now();
+
+    let mut tasks = JoinSet::new();
+    for task in 0..concurrency {
+        let target = target.clone();
+        // the first tasks take the remainder
+        let signs = n_signs / concurrency + u64::from(task < n_signs % concurrency);
+
+        tasks.spawn(async move {
+            let mut latencies = Vec::with_capacity(signs as usize);
+            for _ in 0..signs {
+                let object_root = rand::random::<[u8; 32]>();
+
+                let start = Instant::now();
+                target.sign(&object_root).await;
+                latencies.push(start.elapsed().as_micros() as u64);
+            }
+            latencies
+        });
+    }
+
+    // max ~1s
+    let mut histo = Histogram::new(12, 20).unwrap();
+    while let Some(latencies) = tasks.join_next().await {
+        for latency in latencies.expect("sign task panicked") {
+            histo.increment(latency).unwrap();
+        }
+    }
+
+    PhaseStats { n_signs, elapsed: start.elapsed(), histo }
+}
+
+struct PhaseStats {
+    n_signs: u64,
+    elapsed: Duration,
+    histo: Histogram,
+}
+
+impl PhaseStats {
+    fn throughput(&self) -> f64 {
+        self.n_signs as f64 / self.elapsed.as_secs_f64()
+    }
+
+    /// Latency in ms, empty if no sign was run
+    fn percentile(&self, percentile: f64) -> String {
+        match self.histo.percentile(percentile) {
+            Ok(Some(bucket)) => format!("{:.2}ms", bucket.end() as f64 / 1000.),
+            _ => "-".to_string(),
+        }
+    }
+}
+
+struct BenchResults {
+    id: String,
+    phase: &'static str,
+    stats: PhaseStats,
+}
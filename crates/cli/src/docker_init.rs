@@ -6,14 +6,16 @@ use std::{
 
 use cb_common::{
     config::{
-        CommitBoostConfig, LogsSettings, ModuleKind, SignerConfig, BUILDER_PORT_ENV,
-        BUILDER_URLS_ENV, CHAIN_SPEC_ENV, CONFIG_DEFAULT, CONFIG_ENV, JWTS_ENV, LOGS_DIR_DEFAULT,
-        LOGS_DIR_ENV, METRICS_PORT_ENV, MODULE_ID_ENV, MODULE_JWT_ENV, PBS_ENDPOINT_ENV,
-        PBS_MODULE_NAME, PROXY_DIR_DEFAULT, PROXY_DIR_ENV, PROXY_DIR_KEYS_DEFAULT,
-        PROXY_DIR_KEYS_ENV, PROXY_DIR_SECRETS_DEFAULT, PROXY_DIR_SECRETS_ENV, SIGNER_DEFAULT,
+        CommitBoostConfig, LogsSettings, ModuleKind, SignerConfig, StatusEndpoint,
+        BUILDER_PORT_ENV, BUILDER_URLS_ENV, CHAIN_SPEC_ENV, CONFIG_DEFAULT, CONFIG_ENV, JWTS_ENV,
+        LOGS_DIR_DEFAULT, LOGS_DIR_ENV, METRICS_PORT_ENV, MODULE_ID_ENV, MODULE_JWT_ENV,
+        PBS_ENDPOINT_ENV, PBS_MODULE_NAME, PROXY_DIR_DEFAULT, PROXY_DIR_ENV,
+        PROXY_DIR_KEYS_DEFAULT, PROXY_DIR_KEYS_ENV, PROXY_DIR_SECRETS_DEFAULT,
+        PROXY_DIR_SECRETS_ENV, PROXY_STORE_PASSWORD_ENV, SIGNER_ADMIN_JWT_ENV, SIGNER_DEFAULT,
         SIGNER_DIR_KEYS_DEFAULT, SIGNER_DIR_KEYS_ENV, SIGNER_DIR_SECRETS_DEFAULT,
-        SIGNER_DIR_SECRETS_ENV, SIGNER_KEYS_ENV, SIGNER_MODULE_NAME, SIGNER_PORT_ENV,
-        SIGNER_URL_ENV,
+        SIGNER_DIR_SECRETS_ENV, SIGNER_KEYS_ENV, SIGNER_MODULE_NAME, SIGNER_NONCE_DIR_DEFAULT,
+        SIGNER_NONCE_FILE_ENV, SIGNER_PORT_ENV, SIGNER_STATS_DIR_DEFAULT, SIGNER_STATS_FILE_ENV,
+        SIGNER_UNIX_SOCKET_DIR_DEFAULT, SIGNER_UNIX_SOCKET_ENV, SIGNER_URL_ENV,
     },
     pbs::{BUILDER_API_PATH, GET_STATUS_PATH},
     signer::{ProxyStore, SignerLoader},
@@ -313,7 +315,18 @@ pub async fn handle_docker_init(config_path: String, output_dir: String) -> Resu
     services.insert("cb_pbs".to_owned(), Some(pbs_service));
 
     // setup signer service
-    if let Some(SignerConfig::Local { docker_image, loader, store }) = cb_config.signer {
+    if let Some(SignerConfig::Local {
+        docker_image,
+        loader,
+        store,
+        status_endpoint,
+        unix_socket,
+        persisted_stats,
+        nonce_file,
+        enable_admin_api,
+        ..
+    }) = cb_config.signer
+    {
         if needs_signer_module {
             if metrics_enabled {
                 targets.push(PrometheusTargetConfig {
@@ -342,6 +355,13 @@ pub async fn handle_docker_init(config_path: String, output_dir: String) -> Resu
 
             // write jwts to env
             envs.insert(JWTS_ENV.into(), format_comma_separated(&jwts));
+            // the admin API can export the proxy secret keys, so its JWT is only
+            // generated if it's explicitly enabled
+            if enable_admin_api {
+                let (key, val) = get_env_same(SIGNER_ADMIN_JWT_ENV);
+                signer_envs.insert(key, val);
+                envs.insert(SIGNER_ADMIN_JWT_ENV.into(), random_jwt());
+            }
 
             // volumes
             let mut volumes = vec![config_volume.clone()];
@@ -378,7 +398,7 @@ pub async fn handle_docker_init(config_path: String, output_dir: String) -> Resu
 
             if let Some(store) = store {
                 match store {
-                    ProxyStore::File { proxy_dir } => {
+                    ProxyStore::File { proxy_dir, encrypted } => {
                         volumes.push(Volumes::Simple(format!(
                             "{}:{}:rw",
                             proxy_dir.display(),
@@ -386,6 +406,10 @@ pub async fn handle_docker_init(config_path: String, output_dir: String) -> Resu
                         )));
                         let (k, v) = get_env_val(PROXY_DIR_ENV, PROXY_DIR_DEFAULT);
                         signer_envs.insert(k, v);
+                        if encrypted {
+                            let (k, v) = get_env_same(PROXY_STORE_PASSWORD_ENV);
+                            signer_envs.insert(k, v);
+                        }
                     }
                     ProxyStore::ERC2335 { keys_path, secrets_path } => {
                         volumes.push(Volumes::Simple(format!(
@@ -407,6 +431,51 @@ pub async fn handle_docker_init(config_path: String, output_dir: String) -> Resu
                 }
             }
 
+            // mount the socket's directory so processes on the host can reach it
+            if let Some(socket) = unix_socket {
+                let (Some(dir), Some(file)) = (socket.parent(), socket.file_name()) else {
+                    eyre::bail!("invalid signer unix socket path {socket:?}");
+                };
+                volumes.push(Volumes::Simple(format!(
+                    "{}:{}:rw",
+                    dir.display(),
+                    SIGNER_UNIX_SOCKET_DIR_DEFAULT
+                )));
+                let container_socket = Path::new(SIGNER_UNIX_SOCKET_DIR_DEFAULT).join(file);
+                let (k, v) =
+                    get_env_val(SIGNER_UNIX_SOCKET_ENV, &container_socket.to_string_lossy());
+                signer_envs.insert(k, v);
+            }
+
+            if let Some(stats) = persisted_stats {
+                let (Some(dir), Some(file)) = (stats.path.parent(), stats.path.file_name()) else {
+                    eyre::bail!("invalid signer stats path {:?}", stats.path);
+                };
+                volumes.push(Volumes::Simple(format!(
+                    "{}:{}:rw",
+                    dir.display(),
+                    SIGNER_STATS_DIR_DEFAULT
+                )));
+                let container_stats = Path::new(SIGNER_STATS_DIR_DEFAULT).join(file);
+                let (k, v) = get_env_val(SIGNER_STATS_FILE_ENV, &container_stats.to_string_lossy());
+                signer_envs.insert(k, v);
+            }
+
+            if let Some(path) = nonce_file {
+                let (Some(dir), Some(file)) = (path.parent(), path.file_name()) else {
+                    eyre::bail!("invalid signer nonce file path {path:?}");
+                };
+                volumes.push(Volumes::Simple(format!(
+                    "{}:{}:rw",
+                    dir.display(),
+                    SIGNER_NONCE_DIR_DEFAULT
+                )));
+                let container_nonces = Path::new(SIGNER_NONCE_DIR_DEFAULT).join(file);
+                let (k, v) =
+                    get_env_val(SIGNER_NONCE_FILE_ENV, &container_nonces.to_string_lossy());
+                signer_envs.insert(k, v);
+            }
+
             volumes.extend(get_log_volume(&cb_config.logs, SIGNER_MODULE_NAME));
 
             // networks
@@ -421,7 +490,8 @@ pub async fn handle_docker_init(config_path: String, output_dir: String) -> Resu
                 networks: Networks::Simple(signer_networks),
                 volumes,
                 environment: Environment::KvPair(signer_envs),
-                healthcheck: Some(Healthcheck {
+                // the healthcheck has no JWT, so it only works on a public status endpoint
+                healthcheck: (status_endpoint == StatusEndpoint::Public).then(|| Healthcheck {
                     test: Some(HealthcheckTest::Single(format!(
                         "curl -f http://localhost:{signer_port}/status"
                     ))),
@@ -1,4 +1,10 @@
-use std::sync::Arc;
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
 use alloy::rpc::types::beacon::BlsSignature;
 use eyre::WrapErr;
@@ -7,41 +13,121 @@ use serde::Deserialize;
 use url::Url;
 
 use super::{
-    constants::{GENERATE_PROXY_KEY_PATH, GET_PUBKEYS_PATH, REQUEST_SIGNATURE_PATH},
+    constants::{
+        CHECK_KEYS_PATH, DELETE_PROXY_KEYS_PATH, GENERATE_PROXY_KEY_PATH, GET_PUBKEYS_PATH,
+        PERMISSIONS_PATH, PROOF_OF_POSSESSION_PATH, REQUEST_NONCE_HEADER, REQUEST_SIGNATURE_PATH,
+        SIGN_VALIDATOR_REGISTRATION_PATH,
+    },
     error::SignerClientError,
     request::{
-        EncryptionScheme, GenerateProxyRequest, GetPubkeysResponse, PublicKey,
-        SignConsensusRequest, SignProxyRequest, SignRequest, SignedProxyDelegation,
+        CheckKeysRequest, CheckKeysResponse, DeleteProxyKeysRequest, DeleteProxyKeysResponse,
+        EncryptionScheme, GenerateProxyRequest, GenerateProxyResponse, GetPubkeysResponse,
+        ModulePermissions, ProofOfPossessionRequest, ProxyPublicKey, PublicKey,
+        SignConsensusRequest, SignProxyRequest, SignRequest, SignatureWithRoot,
+        SignedProxyDelegation, ValidatorRegistrationMessage,
     },
 };
 use crate::{
     signer::{BlsPublicKey, EcdsaPublicKey, EcdsaSignature},
+    utils::utcnow_us,
     DEFAULT_REQUEST_TIMEOUT,
 };
 
+/// Idle connections kept open to the signer, enough for a module signing a
+/// few requests concurrently
+pub const SIGNER_CLIENT_POOL_SIZE_DEFAULT: usize = 8;
+/// How long an idle connection to the signer is kept open
+pub const SIGNER_CLIENT_POOL_IDLE_TIMEOUT_DEFAULT: Duration = Duration::from_secs(90);
+
 /// Client used by commit modules to request signatures via the Signer API
 #[derive(Debug, Clone)]
 pub struct SignerClient {
     /// Url endpoint of the Signer Module
     url: Arc<Url>,
     client: reqwest::Client,
+    /// Last nonce sent with a sign request, if the signer requires nonces
+    last_nonce: Option<Arc<AtomicU64>>,
 }
 
-impl SignerClient {
-    /// Create a new SignerClient
-    pub fn new(signer_server_url: Url, jwt: &str) -> eyre::Result<Self> {
+/// Options of a [`SignerClient`]. Connections are kept alive and reused
+/// between requests, so modules with many concurrent signs can raise the
+/// pool size to avoid opening new connections
+#[derive(Debug, Clone)]
+pub struct SignerClientBuilder {
+    url: Url,
+    jwt: String,
+    timeout: Duration,
+    pool_size: usize,
+    pool_idle_timeout: Duration,
+    nonces: bool,
+}
+
+impl SignerClientBuilder {
+    pub fn new(signer_server_url: Url, jwt: &str) -> Self {
+        Self {
+            url: signer_server_url,
+            jwt: jwt.to_string(),
+            timeout: DEFAULT_REQUEST_TIMEOUT,
+            pool_size: SIGNER_CLIENT_POOL_SIZE_DEFAULT,
+            pool_idle_timeout: SIGNER_CLIENT_POOL_IDLE_TIMEOUT_DEFAULT,
+            nonces: false,
+        }
+    }
+
+    /// Timeout of each request, defaults to 12 seconds
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        Self { timeout, ..self }
+    }
+
+    /// Max idle connections kept open, defaults to
+    /// [`SIGNER_CLIENT_POOL_SIZE_DEFAULT`]
+    pub fn with_pool_size(self, pool_size: usize) -> Self {
+        Self { pool_size, ..self }
+    }
+
+    /// How long idle connections are kept open, defaults to
+    /// [`SIGNER_CLIENT_POOL_IDLE_TIMEOUT_DEFAULT`]
+    pub fn with_pool_idle_timeout(self, pool_idle_timeout: Duration) -> Self {
+        Self { pool_idle_timeout, ..self }
+    }
+
+    /// Send a nonce with every sign request, needed if the signer has a
+    /// `nonce_file`. Nonces are derived from the current time so they keep
+    /// increasing across restarts of the module. Concurrent requests can arrive
+    /// out of order, which is only accepted if the signer sets a
+    /// `nonce_window`
+    pub fn with_nonces(self, nonces: bool) -> Self {
+        Self { nonces, ..self }
+    }
+
+    pub fn build(self) -> eyre::Result<SignerClient> {
         let mut headers = HeaderMap::new();
 
         let mut auth_value =
-            HeaderValue::from_str(&format!("Bearer {}", jwt)).wrap_err("invalid jwt")?;
+            HeaderValue::from_str(&format!("Bearer {}", self.jwt)).wrap_err("invalid jwt")?;
         auth_value.set_sensitive(true);
         headers.insert(AUTHORIZATION, auth_value);
         let client = reqwest::Client::builder()
-            .timeout(DEFAULT_REQUEST_TIMEOUT)
+            .timeout(self.timeout)
+            .pool_max_idle_per_host(self.pool_size)
+            .pool_idle_timeout(self.pool_idle_timeout)
             .default_headers(headers)
             .build()?;
 
-        Ok(Self { url: signer_server_url.into(), client })
+        let last_nonce = self.nonces.then(|| Arc::new(AtomicU64::new(0)));
+
+        Ok(SignerClient { url: self.url.into(), client, last_nonce })
+    }
+}
+
+impl SignerClient {
+    /// Create a new SignerClient with the default options
+    pub fn new(signer_server_url: Url, jwt: &str) -> eyre::Result<Self> {
+        Self::builder(signer_server_url, jwt).build()
+    }
+
+    pub fn builder(signer_server_url: Url, jwt: &str) -> SignerClientBuilder {
+        SignerClientBuilder::new(signer_server_url, jwt)
     }
 
     /// Request a list of validator pubkeys for which signatures can be
@@ -61,13 +147,103 @@ impl SignerClient {
         Ok(serde_json::from_slice(&res.bytes().await?)?)
     }
 
+    /// Returns what this module is allowed to do, to fail early instead of
+    /// getting rejected by the signer
+    pub async fn get_permissions(&self) -> Result<ModulePermissions, SignerClientError> {
+        let url = self.url.join(PERMISSIONS_PATH)?;
+        let res = self.client.get(url).send().await?;
+
+        if !res.status().is_success() {
+            return Err(SignerClientError::FailedRequest {
+                status: res.status().as_u16(),
+                error_msg: String::from_utf8_lossy(&res.bytes().await?).into_owned(),
+            });
+        }
+
+        Ok(serde_json::from_slice(&res.bytes().await?)?)
+    }
+
+    /// Checks which of the given keys this module can sign with
+    pub async fn check_keys(
+        &self,
+        pubkeys: Vec<ProxyPublicKey>,
+    ) -> Result<CheckKeysResponse, SignerClientError> {
+        let url = self.url.join(CHECK_KEYS_PATH)?;
+        let request = CheckKeysRequest { pubkeys };
+        let res = self.client.post(url).json(&request).send().await?;
+
+        let status = res.status();
+        let response_bytes = res.bytes().await?;
+
+        if !status.is_success() {
+            return Err(SignerClientError::FailedRequest {
+                status: status.as_u16(),
+                error_msg: String::from_utf8_lossy(&response_bytes).into_owned(),
+            });
+        }
+
+        let check_response = serde_json::from_slice(&response_bytes)?;
+
+        Ok(check_response)
+    }
+
     /// Send a signature request
     async fn request_signature<T>(&self, request: &SignRequest) -> Result<T, SignerClientError>
     where
         T: for<'de> Deserialize<'de>,
     {
         let url = self.url.join(REQUEST_SIGNATURE_PATH)?;
-        let res = self.client.post(url).json(&request).send().await?;
+        self.post_sign_request(url, request).await
+    }
+
+    /// Request a signature for the given slot. The signer records the
+    /// latency of these requests by the phase of the slot they arrive in
+    pub async fn request_signature_for_slot<T>(
+        &self,
+        request: &SignRequest,
+        slot: u64,
+    ) -> Result<T, SignerClientError>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let mut url = self.url.join(REQUEST_SIGNATURE_PATH)?;
+        url.query_pairs_mut().append_pair("slot", &slot.to_string());
+        self.post_sign_request(url, request).await
+    }
+
+    /// Request a signature together with the signing root the signer used,
+    /// to check the signer applied the expected domain
+    pub async fn request_signature_with_root<T>(
+        &self,
+        request: &SignRequest,
+    ) -> Result<SignatureWithRoot<T>, SignerClientError>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let mut url = self.url.join(REQUEST_SIGNATURE_PATH)?;
+        url.query_pairs_mut().append_pair("include_signing_root", "true");
+        self.post_sign_request(url, request).await
+    }
+
+    /// Adds a nonce to a sign request, if the signer requires them
+    fn with_nonce(&self, builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.last_nonce {
+            Some(last_nonce) => {
+                builder.header(REQUEST_NONCE_HEADER, next_nonce(last_nonce).to_string())
+            }
+            None => builder,
+        }
+    }
+
+    async fn post_sign_request<T>(
+        &self,
+        url: Url,
+        request: &SignRequest,
+    ) -> Result<T, SignerClientError>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let res = self.with_nonce(self.client.post(url).json(request)).send().await?;
 
         let status = res.status();
         let response_bytes = res.bytes().await?;
@@ -105,10 +281,71 @@ impl SignerClient {
         self.request_signature(&request.into()).await
     }
 
+    /// Request a validator registration signature with the builder domain. The
+    /// module must be listed in the signer `registration_modules`
+    pub async fn request_registration_signature(
+        &self,
+        message: &ValidatorRegistrationMessage,
+    ) -> Result<BlsSignature, SignerClientError> {
+        let url = self.url.join(SIGN_VALIDATOR_REGISTRATION_PATH)?;
+        let res = self.with_nonce(self.client.post(url).json(message)).send().await?;
+
+        let status = res.status();
+        let response_bytes = res.bytes().await?;
+
+        if !status.is_success() {
+            return Err(SignerClientError::FailedRequest {
+                status: status.as_u16(),
+                error_msg: String::from_utf8_lossy(&response_bytes).into_owned(),
+            });
+        }
+
+        let signature = serde_json::from_slice(&response_bytes)?;
+
+        Ok(signature)
+    }
+
+    /// Request a proof of possession of a consensus key or of a BLS proxy of
+    /// the module. The module must be listed in the signer `pop_modules`
+    pub async fn request_proof_of_possession(
+        &self,
+        pubkey: BlsPublicKey,
+    ) -> Result<BlsSignature, SignerClientError> {
+        let url = self.url.join(PROOF_OF_POSSESSION_PATH)?;
+        let request = ProofOfPossessionRequest { pubkey };
+        let res = self.with_nonce(self.client.post(url).json(&request)).send().await?;
+
+        let status = res.status();
+        let response_bytes = res.bytes().await?;
+
+        if !status.is_success() {
+            return Err(SignerClientError::FailedRequest {
+                status: status.as_u16(),
+                error_msg: String::from_utf8_lossy(&response_bytes).into_owned(),
+            });
+        }
+
+        let signature = serde_json::from_slice(&response_bytes)?;
+
+        Ok(signature)
+    }
+
     async fn generate_proxy_key<T>(
         &self,
         request: &GenerateProxyRequest,
     ) -> Result<SignedProxyDelegation<T>, SignerClientError>
+    where
+        T: PublicKey + for<'de> Deserialize<'de>,
+    {
+        Ok(self.generate_proxy(request).await?.delegation)
+    }
+
+    /// Generate a proxy, returning its pubkey, scheme and module together
+    /// with the delegation
+    pub async fn generate_proxy<T>(
+        &self,
+        request: &GenerateProxyRequest,
+    ) -> Result<GenerateProxyResponse<T>, SignerClientError>
     where
         T: PublicKey + for<'de> Deserialize<'de>,
     {
@@ -125,9 +362,9 @@ impl SignerClient {
             });
         }
 
-        let signed_proxy_delegation = serde_json::from_slice(&response_bytes)?;
+        let generated_proxy = serde_json::from_slice(&response_bytes)?;
 
-        Ok(signed_proxy_delegation)
+        Ok(generated_proxy)
     }
 
     pub async fn generate_proxy_key_bls(
@@ -151,4 +388,63 @@ impl SignerClient {
 
         Ok(ecdsa_signed_proxy_delegation)
     }
+
+    /// Like `generate_proxy_key_bls`, but also succeeds if the signer has no
+    /// proxy store. The proxy is lost when the signer restarts
+    pub async fn generate_ephemeral_proxy_key_bls(
+        &self,
+        consensus_pubkey: BlsPublicKey,
+    ) -> Result<SignedProxyDelegation<BlsPublicKey>, SignerClientError> {
+        let request =
+            GenerateProxyRequest::new(consensus_pubkey, EncryptionScheme::Bls).with_ephemeral(true);
+
+        self.generate_proxy_key(&request).await
+    }
+
+    /// Like `generate_proxy_key_ecdsa`, but also succeeds if the signer has no
+    /// proxy store. The proxy is lost when the signer restarts
+    pub async fn generate_ephemeral_proxy_key_ecdsa(
+        &self,
+        consensus_pubkey: BlsPublicKey,
+    ) -> Result<SignedProxyDelegation<EcdsaPublicKey>, SignerClientError> {
+        let request = GenerateProxyRequest::new(consensus_pubkey, EncryptionScheme::Ecdsa)
+            .with_ephemeral(true);
+
+        self.generate_proxy_key(&request).await
+    }
+
+    /// Deletes proxy keys of this module, returning the status of each key in
+    /// the same order
+    pub async fn delete_proxy_keys(
+        &self,
+        pubkeys: Vec<ProxyPublicKey>,
+    ) -> Result<DeleteProxyKeysResponse, SignerClientError> {
+        let url = self.url.join(DELETE_PROXY_KEYS_PATH)?;
+        let request = DeleteProxyKeysRequest { pubkeys };
+        let res = self.client.delete(url).json(&request).send().await?;
+
+        let status = res.status();
+        let response_bytes = res.bytes().await?;
+
+        if !status.is_success() {
+            return Err(SignerClientError::FailedRequest {
+                status: status.as_u16(),
+                error_msg: String::from_utf8_lossy(&response_bytes).into_owned(),
+            });
+        }
+
+        let delete_response = serde_json::from_slice(&response_bytes)?;
+
+        Ok(delete_response)
+    }
+}
+
+/// Returns a nonce higher than the last one, and at least the current time in
+/// microseconds
+fn next_nonce(last_nonce: &AtomicU64) -> u64 {
+    let now = utcnow_us();
+    let previous = last_nonce
+        .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |last| Some(now.max(last + 1)))
+        .expect("the update always returns a nonce");
+    now.max(previous + 1)
 }
@@ -1,4 +1,28 @@
 pub const GET_PUBKEYS_PATH: &str = "/signer/v1/get_pubkeys";
 pub const REQUEST_SIGNATURE_PATH: &str = "/signer/v1/request_signature";
 pub const GENERATE_PROXY_KEY_PATH: &str = "/signer/v1/generate_proxy_key";
+pub const SIGN_VALIDATOR_REGISTRATION_PATH: &str = "/signer/v1/sign_validator_registration";
+pub const DELETE_PROXY_KEYS_PATH: &str = "/signer/v1/delete_proxy_keys";
+pub const CHECK_KEYS_PATH: &str = "/signer/v1/check_keys";
+pub const PROOF_OF_POSSESSION_PATH: &str = "/signer/v1/proof_of_possession";
+pub const REQUEST_EXAMPLES_PATH: &str = "/signer/v1/examples";
+pub const PERMISSIONS_PATH: &str = "/signer/v1/permissions";
 pub const STATUS_PATH: &str = "/status";
+/// Header used to correlate the logs of a module with the ones of the signer
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+/// Header carrying the nonce of a sign request, if the signer tracks nonces
+pub const REQUEST_NONCE_HEADER: &str = "x-request-nonce";
+/// Content type of SSZ encoded sign requests and signatures
+pub const SSZ_CONTENT_TYPE: &str = "application/octet-stream";
+/// Content type of get_pubkeys streamed as one JSON object per line
+pub const NDJSON_CONTENT_TYPE: &str = "application/x-ndjson";
+pub const SIGNER_CONFIG_PATH: &str = "/config";
+pub const SIGNER_RELOAD_PATH: &str = "/reload";
+pub const SIGNER_ALL_PUBKEYS_PATH: &str = "/pubkeys";
+pub const SIGNER_EXPORT_PROXIES_PATH: &str = "/proxies/export";
+pub const SIGNER_IMPORT_PROXIES_PATH: &str = "/proxies/import";
+pub const SIGNER_REDELEGATE_PROXIES_PATH: &str = "/proxies/redelegate";
+pub const SIGNER_MODULE_DENYLIST_PATH: &str = "/modules/denylist";
+pub const SIGNER_KEY_USAGE_PATH: &str = "/keys/usage";
+pub const SIGNER_REENCRYPT_KEYSTORES_PATH: &str = "/keystores/reencrypt";
+pub const SIGNER_STATS_PATH: &str = "/stats";
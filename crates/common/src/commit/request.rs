@@ -1,20 +1,28 @@
 use std::{
+    collections::HashMap,
     fmt::{self, Debug, Display, LowerHex},
     str::FromStr,
 };
 
-use alloy::rpc::types::beacon::BlsSignature;
+use alloy::{
+    primitives::{Address, B256},
+    rpc::types::beacon::{
+        constants::BLS_PUBLIC_KEY_BYTES_LEN, BlsPublicKey as BlsPublicKeyInner, BlsSignature,
+    },
+};
 use derive_more::derive::From;
-use serde::{Deserialize, Serialize};
+use eyre::{bail, ensure, OptionExt};
+use serde::{ser::SerializeStruct, Deserialize, Serialize, Serializer};
 use tree_hash::TreeHash;
 use tree_hash_derive::TreeHash;
 
 use crate::{
-    constants::COMMIT_BOOST_DOMAIN,
+    config::SignatureQuota,
+    constants::{COMMIT_BOOST_DOMAIN, GENESIS_VALIDATORS_ROOT},
     error::BlstErrorWrapper,
-    signature::verify_signed_message,
+    signature::verify_signed_root,
     signer::{BlsPublicKey, EcdsaPublicKey},
-    types::Chain,
+    types::{Chain, ModuleId},
 };
 
 pub trait PublicKey: AsRef<[u8]> + Debug + Clone + Copy + TreeHash + Display + LowerHex {}
@@ -39,6 +47,78 @@ impl<T: PublicKey> fmt::Display for ProxyDelegation<T> {
     }
 }
 
+impl<T: PublicKey> ProxyDelegation<T> {
+    /// Root signed by the delegator, depending on the delegation version
+    pub fn object_root(&self, version: DelegationVersion) -> [u8; 32] {
+        match version {
+            DelegationVersion::V1 => self.tree_hash_root().0,
+            DelegationVersion::V2 => {
+                VersionedProxyDelegation {
+                    version: version.into(),
+                    delegator: self.delegator,
+                    proxy: self.proxy,
+                }
+                .tree_hash_root()
+                .0
+            }
+        }
+    }
+}
+
+/// Message signed for a V2 delegation, the version is part of the root so a
+/// signature can't be replayed as another version
+#[derive(TreeHash)]
+struct VersionedProxyDelegation<T: PublicKey> {
+    version: u64,
+    delegator: BlsPublicKey,
+    proxy: T,
+}
+
+/// Version of a proxy delegation, serialized as a number. Unknown versions are
+/// rejected instead of being verified as the latest one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(try_from = "u64", into = "u64")]
+pub enum DelegationVersion {
+    /// Root of the delegation message
+    #[default]
+    V1,
+    /// Root of the delegation message together with its version
+    V2,
+}
+
+impl DelegationVersion {
+    pub fn is_v1(&self) -> bool {
+        *self == DelegationVersion::V1
+    }
+}
+
+impl TryFrom<u64> for DelegationVersion {
+    type Error = eyre::Report;
+
+    fn try_from(version: u64) -> Result<Self, Self::Error> {
+        match version {
+            1 => Ok(DelegationVersion::V1),
+            2 => Ok(DelegationVersion::V2),
+            _ => bail!("unsupported delegation version {version}"),
+        }
+    }
+}
+
+impl From<DelegationVersion> for u64 {
+    fn from(version: DelegationVersion) -> Self {
+        match version {
+            DelegationVersion::V1 => 1,
+            DelegationVersion::V2 => 2,
+        }
+    }
+}
+
+impl fmt::Display for DelegationVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", u64::from(*self))
+    }
+}
+
 // TODO: might need to adapt the SignedProxyDelegation so that it goes through
 // web3 signer
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -46,6 +126,10 @@ pub struct SignedProxyDelegation<T: PublicKey> {
     pub message: ProxyDelegation<T>,
     /// Signature of message with the delegator keypair
     pub signature: BlsSignature,
+    /// Decides the root that was signed, omitted for V1 so older modules can
+    /// still read the delegation
+    #[serde(default, skip_serializing_if = "DelegationVersion::is_v1")]
+    pub version: DelegationVersion,
 }
 
 pub type SignedProxyDelegationBls = SignedProxyDelegation<BlsPublicKey>;
@@ -53,10 +137,10 @@ pub type SignedProxyDelegationEcdsa = SignedProxyDelegation<EcdsaPublicKey>;
 
 impl<T: PublicKey> SignedProxyDelegation<T> {
     pub fn validate(&self, chain: Chain) -> Result<(), BlstErrorWrapper> {
-        verify_signed_message(
+        verify_signed_root(
             chain,
             &self.message.delegator,
-            &self.message,
+            self.message.object_root(self.version),
             &self.signature,
             COMMIT_BOOST_DOMAIN,
         )
@@ -78,6 +162,72 @@ pub enum SignRequest {
     ProxyEcdsa(SignProxyRequest<EcdsaPublicKey>),
 }
 
+impl SignRequest {
+    const CONSENSUS_SELECTOR: u8 = 0;
+    const PROXY_BLS_SELECTOR: u8 = 1;
+    const PROXY_ECDSA_SELECTOR: u8 = 2;
+
+    /// Encodes the request as an SSZ union: a selector byte followed by the
+    /// pubkey and the object root
+    pub fn as_ssz_bytes(&self) -> Vec<u8> {
+        let (selector, pubkey, object_root) = match self {
+            SignRequest::Consensus(req) => {
+                (Self::CONSENSUS_SELECTOR, req.pubkey.as_slice(), &req.object_root)
+            }
+            SignRequest::ProxyBls(req) => {
+                (Self::PROXY_BLS_SELECTOR, req.pubkey.as_slice(), &req.object_root)
+            }
+            SignRequest::ProxyEcdsa(req) => {
+                (Self::PROXY_ECDSA_SELECTOR, req.pubkey.as_ref(), &req.object_root)
+            }
+        };
+
+        let mut bytes = Vec::with_capacity(1 + pubkey.len() + object_root.len());
+        bytes.push(selector);
+        bytes.extend_from_slice(pubkey);
+        bytes.extend_from_slice(object_root);
+        bytes
+    }
+
+    /// Decodes a request encoded with [`SignRequest::as_ssz_bytes`]
+    pub fn from_ssz_bytes(bytes: &[u8]) -> eyre::Result<Self> {
+        let (selector, rest) = bytes.split_first().ok_or_eyre("empty SSZ sign request")?;
+
+        let pubkey_len = match *selector {
+            Self::CONSENSUS_SELECTOR | Self::PROXY_BLS_SELECTOR => BLS_PUBLIC_KEY_BYTES_LEN,
+            Self::PROXY_ECDSA_SELECTOR => EcdsaPublicKey::SIZE,
+            _ => bail!("invalid SSZ union selector: {selector}"),
+        };
+
+        ensure!(
+            rest.len() == pubkey_len + 32,
+            "invalid SSZ sign request length: expected {}, got {}",
+            pubkey_len + 32,
+            rest.len()
+        );
+
+        let (pubkey, object_root) = rest.split_at(pubkey_len);
+        let object_root: [u8; 32] = object_root.try_into()?;
+
+        let request = match *selector {
+            Self::PROXY_ECDSA_SELECTOR => {
+                let pubkey: [u8; EcdsaPublicKey::SIZE] = pubkey.try_into()?;
+                SignProxyRequest::new(EcdsaPublicKey::from(pubkey), object_root).into()
+            }
+            selector => {
+                let pubkey: BlsPublicKey = BlsPublicKeyInner::from_slice(pubkey).into();
+                if selector == Self::CONSENSUS_SELECTOR {
+                    SignConsensusRequest::new(pubkey, object_root).into()
+                } else {
+                    SignProxyRequest::new(pubkey, object_root).into()
+                }
+            }
+        };
+
+        Ok(request)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SignConsensusRequest {
     pub pubkey: BlsPublicKey,
@@ -128,6 +278,14 @@ impl<T: PublicKey> SignProxyRequest<T> {
     }
 }
 
+/// Signature together with the signing root the signer computed from the
+/// object root and the domain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignatureWithRoot<T> {
+    pub signature: T,
+    pub signing_root: B256,
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum EncryptionScheme {
     #[serde(rename = "bls")]
@@ -163,17 +321,214 @@ pub struct GenerateProxyRequest {
     #[serde(rename = "pubkey")]
     pub consensus_pubkey: BlsPublicKey,
     pub scheme: EncryptionScheme,
+    /// Generate the proxy even if the signer has no proxy store, in which case
+    /// it's lost on restart
+    #[serde(default)]
+    pub ephemeral: bool,
+    /// Version of the delegation to sign, V1 if not set
+    #[serde(default)]
+    pub version: DelegationVersion,
 }
 
 impl GenerateProxyRequest {
     pub fn new(consensus_pubkey: BlsPublicKey, scheme: EncryptionScheme) -> Self {
-        GenerateProxyRequest { consensus_pubkey, scheme }
+        GenerateProxyRequest {
+            consensus_pubkey,
+            scheme,
+            ephemeral: false,
+            version: DelegationVersion::default(),
+        }
+    }
+
+    pub fn with_ephemeral(self, ephemeral: bool) -> Self {
+        Self { ephemeral, ..self }
+    }
+
+    pub fn with_version(self, version: DelegationVersion) -> Self {
+        Self { version, ..self }
     }
 }
 
+/// Proxy generated for a module. The delegation is flattened so the response
+/// can still be read as a `SignedProxyDelegation`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenerateProxyResponse<T: PublicKey> {
+    pub proxy: T,
+    pub scheme: EncryptionScheme,
+    pub module_id: ModuleId,
+    #[serde(flatten)]
+    pub delegation: SignedProxyDelegation<T>,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct GetPubkeysResponse {
     pub keys: Vec<ConsensusProxyMap>,
+    /// Only returned if the signer is configured to report orphan proxies
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub orphans: Option<OrphanProxies>,
+}
+
+/// Hex encoding of the pubkeys in the pubkeys responses, some non-Rust clients
+/// only accept one of the two
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PubkeyEncoding {
+    /// `0x`-prefixed hex
+    #[default]
+    Prefixed,
+    /// Hex without the `0x` prefix
+    Bare,
+}
+
+impl PubkeyEncoding {
+    /// Wraps a response so its pubkeys are serialized in this encoding
+    pub fn encode<T: EncodePubkeys>(self, response: &T) -> PubkeyEncoded<'_, T> {
+        PubkeyEncoded { response, encoding: self }
+    }
+
+    fn hex(self, bytes: &[u8]) -> String {
+        match self {
+            PubkeyEncoding::Prefixed => alloy::hex::encode_prefixed(bytes),
+            PubkeyEncoding::Bare => alloy::hex::encode(bytes),
+        }
+    }
+}
+
+/// Responses with pubkeys, serialized field by field so only the pubkeys
+/// follow the encoding and other strings, like module IDs, are left as is
+pub trait EncodePubkeys {
+    fn serialize_encoded<S: Serializer>(
+        &self,
+        encoding: PubkeyEncoding,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>;
+}
+
+/// A response serialized with the pubkeys in the given encoding
+pub struct PubkeyEncoded<'a, T> {
+    response: &'a T,
+    encoding: PubkeyEncoding,
+}
+
+impl<T: EncodePubkeys> Serialize for PubkeyEncoded<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.response.serialize_encoded(self.encoding, serializer)
+    }
+}
+
+/// A single pubkey in the given encoding
+struct EncodedPubkey<'a, K>(&'a K, PubkeyEncoding);
+
+impl<K: AsRef<[u8]>> Serialize for EncodedPubkey<'_, K> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.1.hex(self.0.as_ref()))
+    }
+}
+
+/// A list of pubkeys in the given encoding
+struct EncodedPubkeys<'a, K>(&'a [K], PubkeyEncoding);
+
+impl<K: AsRef<[u8]>> Serialize for EncodedPubkeys<'_, K> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter().map(|pubkey| EncodedPubkey(pubkey, self.1)))
+    }
+}
+
+impl<T: EncodePubkeys> EncodePubkeys for Vec<T> {
+    fn serialize_encoded<S: Serializer>(
+        &self,
+        encoding: PubkeyEncoding,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter().map(|item| encoding.encode(item)))
+    }
+}
+
+impl<K: Serialize, V: EncodePubkeys> EncodePubkeys for HashMap<K, V> {
+    fn serialize_encoded<S: Serializer>(
+        &self,
+        encoding: PubkeyEncoding,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.iter().map(|(key, value)| (key, encoding.encode(value))))
+    }
+}
+
+impl EncodePubkeys for GetPubkeysResponse {
+    fn serialize_encoded<S: Serializer>(
+        &self,
+        encoding: PubkeyEncoding,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let len = if self.orphans.is_some() { 2 } else { 1 };
+        let mut state = serializer.serialize_struct("GetPubkeysResponse", len)?;
+        state.serialize_field("keys", &encoding.encode(&self.keys))?;
+        if let Some(orphans) = &self.orphans {
+            state.serialize_field("orphans", &encoding.encode(orphans))?;
+        }
+        state.end()
+    }
+}
+
+impl EncodePubkeys for OrphanProxies {
+    fn serialize_encoded<S: Serializer>(
+        &self,
+        encoding: PubkeyEncoding,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("OrphanProxies", 2)?;
+        state.serialize_field("proxy_bls", &EncodedPubkeys(&self.proxy_bls, encoding))?;
+        state.serialize_field("proxy_ecdsa", &EncodedPubkeys(&self.proxy_ecdsa, encoding))?;
+        state.end()
+    }
+}
+
+impl EncodePubkeys for ConsensusProxyMap {
+    fn serialize_encoded<S: Serializer>(
+        &self,
+        encoding: PubkeyEncoding,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let len = if self.proxy_created_at.is_empty() { 4 } else { 5 };
+        let mut state = serializer.serialize_struct("ConsensusProxyMap", len)?;
+        state.serialize_field("consensus", &EncodedPubkey(&self.consensus, encoding))?;
+        state.serialize_field("proxy_bls", &EncodedPubkeys(&self.proxy_bls, encoding))?;
+        state.serialize_field("proxy_ecdsa", &EncodedPubkeys(&self.proxy_ecdsa, encoding))?;
+        state.serialize_field("has_more_proxies", &self.has_more_proxies)?;
+        if !self.proxy_created_at.is_empty() {
+            let created_at = EncodedCreatedAt(&self.proxy_created_at, encoding);
+            state.serialize_field("proxy_created_at", &created_at)?;
+        }
+        state.end()
+    }
+}
+
+/// Proxy creation times keyed by the encoded pubkey
+struct EncodedCreatedAt<'a>(&'a HashMap<ProxyPublicKey, u64>, PubkeyEncoding);
+
+impl Serialize for EncodedCreatedAt<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(
+            self.0.iter().map(|(pubkey, created_at)| (EncodedPubkey(pubkey, self.1), created_at)),
+        )
+    }
+}
+
+/// Proxies delegated by a consensus key that is not loaded in the signer
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct OrphanProxies {
+    pub proxy_bls: Vec<BlsPublicKey>,
+    pub proxy_ecdsa: Vec<EcdsaPublicKey>,
+}
+
+impl OrphanProxies {
+    pub fn len(&self) -> usize {
+        self.proxy_bls.len() + self.proxy_ecdsa.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 }
 
 /// Map of consensus pubkeys to proxies
@@ -182,10 +537,405 @@ pub struct ConsensusProxyMap {
     pub consensus: BlsPublicKey,
     pub proxy_bls: Vec<BlsPublicKey>,
     pub proxy_ecdsa: Vec<EcdsaPublicKey>,
+    /// Some proxies were left out because of the signer
+    /// `max_proxies_per_key`
+    #[serde(default)]
+    pub has_more_proxies: bool,
+    /// Unix timestamp in seconds of when each listed proxy was generated,
+    /// proxies stored before it was recorded are missing
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub proxy_created_at: HashMap<ProxyPublicKey, u64>,
 }
 
 impl ConsensusProxyMap {
     pub fn new(consensus: BlsPublicKey) -> Self {
-        Self { consensus, proxy_bls: vec![], proxy_ecdsa: vec![] }
+        Self {
+            consensus,
+            proxy_bls: vec![],
+            proxy_ecdsa: vec![],
+            has_more_proxies: false,
+            proxy_created_at: HashMap::new(),
+        }
+    }
+
+    /// Drops the creation times of the proxies that are no longer listed
+    pub fn retain_listed_created_at(&mut self) {
+        let Self { proxy_bls, proxy_ecdsa, proxy_created_at, .. } = self;
+        proxy_created_at.retain(|pubkey, _| match pubkey {
+            ProxyPublicKey::Bls(pubkey) => proxy_bls.contains(pubkey),
+            ProxyPublicKey::Ecdsa(pubkey) => proxy_ecdsa.contains(pubkey),
+        });
+    }
+}
+
+/// Builder API validator registration, signed with the application builder
+/// domain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TreeHash)]
+pub struct ValidatorRegistrationMessage {
+    pub fee_recipient: Address,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub gas_limit: u64,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub timestamp: u64,
+    pub pubkey: BlsPublicKey,
+}
+
+/// Request for a proof of possession of a consensus key or of a BLS proxy
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ProofOfPossessionRequest {
+    pub pubkey: BlsPublicKey,
+}
+
+/// A proxy pubkey of either scheme, BLS and ECDSA keys have different lengths
+/// so they can be told apart when decoding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, From)]
+#[serde(untagged)]
+pub enum ProxyPublicKey {
+    Bls(BlsPublicKey),
+    Ecdsa(EcdsaPublicKey),
+}
+
+impl AsRef<[u8]> for ProxyPublicKey {
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            ProxyPublicKey::Bls(pubkey) => pubkey.as_ref(),
+            ProxyPublicKey::Ecdsa(pubkey) => pubkey.as_ref(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckKeysRequest {
+    pub pubkeys: Vec<ProxyPublicKey>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyKind {
+    Consensus,
+    ProxyBls,
+    ProxyEcdsa,
+}
+
+/// Whether the module can sign with a key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyStatus {
+    pub pubkey: ProxyPublicKey,
+    pub available: bool,
+    /// How the key would be used to sign, if available
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<KeyKind>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckKeysResponse {
+    pub keys: Vec<KeyStatus>,
+}
+
+/// What a module is allowed to do, from the signer config
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModulePermissions {
+    pub module_id: ModuleId,
+    /// Persisted proxies can be generated, ephemeral ones always can
+    pub generate_proxy: bool,
+    pub sign_validator_registration: bool,
+    pub proof_of_possession: bool,
+    /// Max signatures per window, if limited
+    pub signature_quota: Option<SignatureQuota>,
+    /// Max proxies of each scheme returned per consensus key by get_pubkeys
+    pub max_proxies_per_key: Option<usize>,
+    /// Max time to produce a signature
+    pub sign_timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteProxyKeysRequest {
+    pub pubkeys: Vec<ProxyPublicKey>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeleteProxyKeyStatus {
+    Deleted,
+    NotFound,
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteProxyKeyResult {
+    pub status: DeleteProxyKeyStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// Follows the keymanager API delete response, with one status per requested
+/// pubkey in the same order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteProxyKeysResponse {
+    pub statuses: Vec<DeleteProxyKeyResult>,
+    /// EIP-3076 interchange JSON for the deleted BLS proxies
+    pub slashing_protection: String,
+}
+
+impl DeleteProxyKeysResponse {
+    /// Proxies sign arbitrary roots and the signer keeps no slashing protection
+    /// history, so the interchange only lists the deleted BLS keys with no
+    /// signed blocks or attestations
+    pub fn new(
+        statuses: Vec<DeleteProxyKeyResult>,
+        deleted_bls: &[BlsPublicKey],
+    ) -> eyre::Result<Self> {
+        let data = deleted_bls
+            .iter()
+            .map(|pubkey| InterchangeData {
+                pubkey: *pubkey,
+                signed_blocks: vec![],
+                signed_attestations: vec![],
+            })
+            .collect();
+        let interchange = Interchange {
+            metadata: InterchangeMetadata {
+                interchange_format_version: "5",
+                genesis_validators_root: B256::from(GENESIS_VALIDATORS_ROOT),
+            },
+            data,
+        };
+
+        Ok(Self { statuses, slashing_protection: serde_json::to_string(&interchange)? })
+    }
+}
+
+#[derive(Serialize)]
+struct Interchange {
+    metadata: InterchangeMetadata,
+    data: Vec<InterchangeData>,
+}
+
+#[derive(Serialize)]
+struct InterchangeMetadata {
+    interchange_format_version: &'static str,
+    genesis_validators_root: B256,
+}
+
+#[derive(Serialize)]
+struct InterchangeData {
+    pubkey: BlsPublicKey,
+    signed_blocks: Vec<()>,
+    signed_attestations: Vec<()>,
+}
+
+/// Example of every request body, built from the request types so it always
+/// matches what the signer accepts
+#[derive(Debug, Clone, Serialize)]
+pub struct RequestExamples {
+    pub sign_consensus: SignRequest,
+    pub sign_proxy_bls: SignRequest,
+    pub sign_proxy_ecdsa: SignRequest,
+    pub generate_proxy_bls: GenerateProxyRequest,
+    pub generate_proxy_ecdsa: GenerateProxyRequest,
+}
+
+impl Default for RequestExamples {
+    fn default() -> Self {
+        let consensus: BlsPublicKey = BlsPublicKeyInner::repeat_byte(0xaa).into();
+        let proxy_bls: BlsPublicKey = BlsPublicKeyInner::repeat_byte(0xbb).into();
+        let proxy_ecdsa = EcdsaPublicKey::from([0x02; EcdsaPublicKey::SIZE]);
+        let object_root = [0x11; 32];
+
+        Self {
+            sign_consensus: SignConsensusRequest::new(consensus, object_root).into(),
+            sign_proxy_bls: SignProxyRequest::new(proxy_bls, object_root).into(),
+            sign_proxy_ecdsa: SignProxyRequest::new(proxy_ecdsa, object_root).into(),
+            generate_proxy_bls: GenerateProxyRequest::new(consensus, EncryptionScheme::Bls),
+            generate_proxy_ecdsa: GenerateProxyRequest::new(consensus, EncryptionScheme::Ecdsa),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{constants::APPLICATION_BUILDER_DOMAIN, signature::verify_signed_message};
+
+    #[test]
+    fn test_sign_request_ssz_roundtrip() {
+        let requests: Vec<SignRequest> = vec![
+            SignConsensusRequest::new(BlsPublicKeyInner::repeat_byte(1).into(), [2; 32]).into(),
+            SignProxyRequest::<BlsPublicKey>::new(
+                BlsPublicKeyInner::repeat_byte(3).into(),
+                [4; 32],
+            )
+            .into(),
+            SignProxyRequest::new(EcdsaPublicKey::from([5; 33]), [6; 32]).into(),
+        ];
+
+        for request in requests {
+            let bytes = request.as_ssz_bytes();
+            let decoded = SignRequest::from_ssz_bytes(&bytes).unwrap();
+
+            assert_eq!(decoded.as_ssz_bytes(), bytes);
+        }
+    }
+
+    #[test]
+    fn test_generate_proxy_response_reads_as_delegation() {
+        let proxy = EcdsaPublicKey::from([2; 33]);
+        let delegation = SignedProxyDelegation {
+            message: ProxyDelegation { delegator: BlsPublicKeyInner::repeat_byte(1).into(), proxy },
+            signature: BlsSignature::repeat_byte(3),
+            version: DelegationVersion::V1,
+        };
+        let response = GenerateProxyResponse {
+            proxy,
+            scheme: EncryptionScheme::Ecdsa,
+            module_id: ModuleId("TEST_MODULE".to_string()),
+            delegation,
+        };
+
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["scheme"], "ecdsa");
+        assert_eq!(json["module_id"], "TEST_MODULE");
+
+        let decoded: SignedProxyDelegationEcdsa = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded.message.proxy, proxy);
+        assert_eq!(decoded.signature, delegation.signature);
+    }
+
+    #[test]
+    fn test_delegation_versions() {
+        let message = ProxyDelegation {
+            delegator: BlsPublicKeyInner::repeat_byte(1).into(),
+            proxy: EcdsaPublicKey::from([2; 33]),
+        };
+
+        assert_eq!(message.object_root(DelegationVersion::V1), message.tree_hash_root().0);
+        assert_ne!(
+            message.object_root(DelegationVersion::V1),
+            message.object_root(DelegationVersion::V2)
+        );
+
+        let delegation = SignedProxyDelegation {
+            message,
+            signature: BlsSignature::repeat_byte(3),
+            version: DelegationVersion::V1,
+        };
+        let json = serde_json::to_value(delegation).unwrap();
+        assert!(json.get("version").is_none());
+
+        let mut json = serde_json::to_value(SignedProxyDelegation {
+            version: DelegationVersion::V2,
+            ..delegation
+        })
+        .unwrap();
+        assert_eq!(json["version"], 2);
+        let decoded: SignedProxyDelegationEcdsa = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(decoded.version, DelegationVersion::V2);
+
+        json["version"] = 3.into();
+        assert!(serde_json::from_value::<SignedProxyDelegationEcdsa>(json).is_err());
+    }
+
+    #[test]
+    fn test_request_examples_roundtrip() {
+        let examples = serde_json::to_value(RequestExamples::default()).unwrap();
+
+        assert_eq!(examples["sign_consensus"]["type"], "consensus");
+        assert_eq!(examples["sign_proxy_ecdsa"]["type"], "proxy_ecdsa");
+
+        for name in ["sign_consensus", "sign_proxy_bls", "sign_proxy_ecdsa"] {
+            let request: SignRequest = serde_json::from_value(examples[name].clone()).unwrap();
+            assert_eq!(serde_json::to_value(request).unwrap(), examples[name]);
+        }
+        for name in ["generate_proxy_bls", "generate_proxy_ecdsa"] {
+            serde_json::from_value::<GenerateProxyRequest>(examples[name].clone()).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_validator_registration_root() {
+        let data = include_str!("../../../../tests/data/registration_holesky.json");
+        let registrations: serde_json::Value = serde_json::from_str(data).unwrap();
+
+        let message: ValidatorRegistrationMessage =
+            serde_json::from_value(registrations[0]["message"].clone()).unwrap();
+        let signature: BlsSignature =
+            serde_json::from_value(registrations[0]["signature"].clone()).unwrap();
+
+        assert!(verify_signed_message(
+            Chain::Holesky,
+            &message.pubkey,
+            &message,
+            &signature,
+            APPLICATION_BUILDER_DOMAIN
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_proxy_public_key_scheme() {
+        let bls: ProxyPublicKey =
+            serde_json::from_str(&format!("\"0x{}\"", "11".repeat(48))).unwrap();
+        let ecdsa: ProxyPublicKey =
+            serde_json::from_str(&format!("\"0x{}\"", "02".repeat(33))).unwrap();
+
+        assert!(matches!(bls, ProxyPublicKey::Bls(_)));
+        assert!(matches!(ecdsa, ProxyPublicKey::Ecdsa(_)));
+        assert!(serde_json::from_str::<ProxyPublicKey>("\"0x1234\"").is_err());
+    }
+
+    #[test]
+    fn test_delete_proxy_keys_response_interchange() {
+        let pubkey: BlsPublicKey = BlsPublicKeyInner::repeat_byte(1).into();
+        let response = DeleteProxyKeysResponse::new(
+            vec![DeleteProxyKeyResult { status: DeleteProxyKeyStatus::Deleted, message: None }],
+            &[pubkey],
+        )
+        .unwrap();
+
+        let interchange: serde_json::Value =
+            serde_json::from_str(&response.slashing_protection).unwrap();
+
+        assert_eq!(interchange["metadata"]["interchange_format_version"], "5");
+        assert_eq!(interchange["data"][0]["pubkey"], pubkey.to_string());
+        assert_eq!(interchange["data"][0]["signed_blocks"].as_array().unwrap().len(), 0);
+    }
+
+    #[test]
+    fn test_pubkey_encoding() {
+        let bls: BlsPublicKey = BlsPublicKeyInner::repeat_byte(1).into();
+        let ecdsa = EcdsaPublicKey::from([2; 33]);
+        let mut keys = ConsensusProxyMap::new(bls);
+        keys.proxy_ecdsa.push(ecdsa);
+        keys.proxy_created_at.insert(ecdsa.into(), 10);
+        let response = GetPubkeysResponse { keys: vec![keys], orphans: None };
+
+        let prefixed = serde_json::to_value(PubkeyEncoding::Prefixed.encode(&response)).unwrap();
+        assert_eq!(prefixed, serde_json::to_value(&response).unwrap());
+        assert_eq!(prefixed["keys"][0]["consensus"], bls.to_string());
+
+        let bare = serde_json::to_value(PubkeyEncoding::Bare.encode(&response)).unwrap();
+        let keys = &bare["keys"][0];
+        assert_eq!(keys["consensus"], "01".repeat(48));
+        assert_eq!(keys["proxy_ecdsa"][0], "02".repeat(33));
+        assert_eq!(keys["proxy_created_at"]["02".repeat(33)], 10);
+        assert_eq!(keys["has_more_proxies"], false);
+
+        // bare hex is still accepted when decoding
+        let decoded: GetPubkeysResponse = serde_json::from_value(bare).unwrap();
+        assert_eq!(decoded.keys[0].consensus, bls);
+        assert_eq!(decoded.keys[0].proxy_created_at[&ProxyPublicKey::Ecdsa(ecdsa)], 10);
+
+        // only pubkeys are affected, not hex-looking module IDs
+        let modules = HashMap::from([(ModuleId("0xabc".to_string()), decoded.keys)]);
+        let bare = serde_json::to_value(PubkeyEncoding::Bare.encode(&modules)).unwrap();
+        assert_eq!(bare["0xabc"][0]["consensus"], "01".repeat(48));
+    }
+
+    #[test]
+    fn test_sign_request_ssz_invalid() {
+        assert!(SignRequest::from_ssz_bytes(&[]).is_err());
+        assert!(SignRequest::from_ssz_bytes(&[3; 81]).is_err());
+        // consensus request with an ecdsa pubkey
+        assert!(SignRequest::from_ssz_bytes(&[0; 66]).is_err());
     }
 }
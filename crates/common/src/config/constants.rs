@@ -14,6 +14,9 @@ pub const METRICS_PORT_ENV: &str = "CB_METRICS_PORT";
 pub const LOGS_DIR_ENV: &str = "CB_LOGS_DIR";
 pub const LOGS_DIR_DEFAULT: &str = "/var/logs/commit-boost";
 
+/// Format of stdout logs, overrides the one in the config
+pub const LOG_FORMAT_ENV: &str = "CB_LOG_FORMAT";
+
 ///////////////////////// PBS /////////////////////////
 
 pub const PBS_IMAGE_DEFAULT: &str = "ghcr.io/commit-boost/pbs:latest";
@@ -34,9 +37,33 @@ pub const SIGNER_MODULE_NAME: &str = "signer";
 
 /// Where the signer module should open the server
 pub const SIGNER_PORT_ENV: &str = "CB_SIGNER_PORT";
+/// Unix socket the signer also serves on, if configured
+pub const SIGNER_UNIX_SOCKET_ENV: &str = "CB_SIGNER_UNIX_SOCKET";
+/// Directory of the unix socket in the signer container
+pub const SIGNER_UNIX_SOCKET_DIR_DEFAULT: &str = "/run/signer";
+/// Permissions of the unix socket if not configured, owner and group only
+pub const SIGNER_UNIX_SOCKET_MODE_DEFAULT: u32 = 0o660;
+/// File the signer persists its stats to, if configured
+pub const SIGNER_STATS_FILE_ENV: &str = "CB_SIGNER_STATS_FILE";
+/// Directory of the stats file in the signer container
+pub const SIGNER_STATS_DIR_DEFAULT: &str = "/var/lib/cb-signer";
+/// File the signer persists the last sign request nonce of each module to, if
+/// configured
+pub const SIGNER_NONCE_FILE_ENV: &str = "CB_SIGNER_NONCE_FILE";
+/// Directory of the nonces file in the signer container
+pub const SIGNER_NONCE_DIR_DEFAULT: &str = "/var/lib/cb-signer-nonces";
 
 /// Comma separated list module_id=jwt_secret
 pub const JWTS_ENV: &str = "CB_JWTS";
+/// Path to toml file with `module_id = "jwt_secret"` entries. If set, takes
+/// priority over `CB_JWTS` and is reloaded when it changes
+pub const JWTS_FILE_ENV: &str = "CB_JWTS_FILE";
+/// Min length of module JWTs, same as the ones generated by the CLI
+pub const JWT_MIN_LENGTH_DEFAULT: usize = 32;
+
+/// JWT to access the admin endpoints of the signer, only used if they're
+/// enabled with `enable_admin_api`
+pub const SIGNER_ADMIN_JWT_ENV: &str = "CB_SIGNER_ADMIN_JWT";
 
 /// Path to json file with plaintext keys (testing only)
 pub const SIGNER_KEYS_ENV: &str = "CB_SIGNER_LOADER_FILE";
@@ -50,6 +77,8 @@ pub const SIGNER_DIR_SECRETS_DEFAULT: &str = "/secrets";
 /// Path to store proxies with plaintext keys (testing only)
 pub const PROXY_DIR_ENV: &str = "CB_PROXY_STORE_DIR";
 pub const PROXY_DIR_DEFAULT: &str = "/proxies";
+/// Password of the proxy file store, if encrypted
+pub const PROXY_STORE_PASSWORD_ENV: &str = "CB_PROXY_STORE_PASSWORD";
 /// Path to store proxy keys
 pub const PROXY_DIR_KEYS_ENV: &str = "CB_PROXY_KEYS_DIR";
 pub const PROXY_DIR_KEYS_DEFAULT: &str = "/proxy_keys";
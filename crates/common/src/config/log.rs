@@ -1,6 +1,6 @@
-use std::path::PathBuf;
+use std::{path::PathBuf, str::FromStr};
 
-use eyre::Result;
+use eyre::{bail, Result};
 use serde::{Deserialize, Serialize};
 
 use super::{load_optional_env_var, CommitBoostConfig, LOGS_DIR_DEFAULT, LOGS_DIR_ENV};
@@ -13,6 +13,34 @@ pub struct LogsSettings {
     pub log_level: String,
     #[serde(default)]
     pub max_log_files: Option<usize>,
+    /// Format of stdout logs, file logs are always JSON
+    #[serde(default)]
+    pub stdout_format: LogFormat,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    /// One line per event
+    #[default]
+    Text,
+    /// Multi-line and colored, for local development
+    Pretty,
+    /// One JSON object per event, for log aggregation
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "text" => Ok(LogFormat::Text),
+            "pretty" => Ok(LogFormat::Pretty),
+            "json" => Ok(LogFormat::Json),
+            _ => bail!("unknown log format: {s}, expected one of text, pretty, json"),
+        }
+    }
 }
 
 impl Default for LogsSettings {
@@ -21,6 +49,7 @@ impl Default for LogsSettings {
             log_dir_path: default_log_dir_path(),
             log_level: default_log_level(),
             max_log_files: None,
+            stdout_format: LogFormat::default(),
         }
     }
 }
@@ -1,6 +1,7 @@
 use std::path::PathBuf;
 
-use eyre::Result;
+use alloy::primitives::hex;
+use eyre::{ensure, Result};
 use serde::{Deserialize, Serialize};
 
 use crate::types::{load_chain_from_file, Chain, ChainLoader, ForkVersion};
@@ -53,23 +54,7 @@ impl CommitBoostConfig {
     pub fn from_env_path() -> Result<Self> {
         let helper_config: HelperConfig = load_file_from_env(CONFIG_ENV)?;
 
-        let chain = match helper_config.chain {
-            ChainLoader::Path { path, genesis_time_secs } => {
-                // check if the file path is overridden by env var
-                let (slot_time_secs, genesis_fork_version) =
-                    if let Some(path) = load_optional_env_var(CHAIN_SPEC_ENV) {
-                        load_chain_from_file(path.parse()?)?
-                    } else {
-                        load_chain_from_file(path)?
-                    };
-                Chain::Custom { genesis_time_secs, slot_time_secs, genesis_fork_version }
-            }
-            ChainLoader::Known(known) => Chain::from(known),
-            ChainLoader::Custom { genesis_time_secs, slot_time_secs, genesis_fork_version } => {
-                let genesis_fork_version: ForkVersion = genesis_fork_version.as_ref().try_into()?;
-                Chain::Custom { genesis_time_secs, slot_time_secs, genesis_fork_version }
-            }
-        };
+        let chain = load_chain(helper_config.chain)?;
 
         let config = CommitBoostConfig {
             chain,
@@ -100,6 +85,50 @@ impl CommitBoostConfig {
     }
 }
 
+/// Resolves the chain from the config file. Every module loading the shared
+/// config must go through this, so that they all end up on the same chain
+fn load_chain(loader: ChainLoader) -> Result<Chain> {
+    let chain = match loader {
+        ChainLoader::Path { path, genesis_time_secs } => {
+            let (slot_time_secs, genesis_fork_version) =
+                load_chain_spec(path, load_optional_env_var(CHAIN_SPEC_ENV))?;
+            Chain::Custom { genesis_time_secs, slot_time_secs, genesis_fork_version }
+        }
+        ChainLoader::Known(known) => Chain::from(known),
+        ChainLoader::Custom { genesis_time_secs, slot_time_secs, genesis_fork_version } => {
+            let genesis_fork_version: ForkVersion = genesis_fork_version.as_ref().try_into()?;
+            Chain::Custom { genesis_time_secs, slot_time_secs, genesis_fork_version }
+        }
+    };
+
+    Ok(chain)
+}
+
+/// Loads the chain spec, the override path (e.g. the spec mounted in the
+/// container) takes priority. If the original path is also reachable, both
+/// specs must match
+fn load_chain_spec(path: PathBuf, override_path: Option<String>) -> Result<(u64, ForkVersion)> {
+    let Some(override_path) = override_path else {
+        return load_chain_from_file(path);
+    };
+
+    let spec = load_chain_from_file(override_path.parse()?)?;
+
+    if path.exists() {
+        let config_spec = load_chain_from_file(path.clone())?;
+        ensure!(
+            config_spec == spec,
+            "chain spec mismatch: {path:?} has slot time {}s and fork version {}, but {CHAIN_SPEC_ENV}={override_path} has slot time {}s and fork version {}",
+            config_spec.0,
+            hex::encode_prefixed(config_spec.1),
+            spec.0,
+            hex::encode_prefixed(spec.1)
+        );
+    }
+
+    Ok(spec)
+}
+
 /// Helper struct to load the chain spec file
 #[derive(Deserialize)]
 struct ChainConfig {
@@ -119,3 +148,37 @@ struct HelperConfig {
     metrics: Option<MetricsConfig>,
     logs: Option<LogsSettings>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec_path(file: &str) -> PathBuf {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.pop();
+        path.pop();
+        path.push("tests/data");
+        path.push(file);
+        path
+    }
+
+    #[test]
+    fn test_load_chain_spec_matching_override() {
+        let path = spec_path("holesky_spec.json");
+        let override_path = path.to_str().map(String::from);
+
+        let spec = load_chain_spec(path.clone(), override_path).unwrap();
+
+        assert_eq!(spec, load_chain_from_file(path).unwrap());
+    }
+
+    #[test]
+    fn test_load_chain_spec_mismatch() {
+        let path = spec_path("holesky_spec.json");
+        let override_path = spec_path("mainnet_spec_data.json").to_str().map(String::from);
+
+        let err = load_chain_spec(path, override_path).unwrap_err();
+
+        assert!(err.to_string().contains("chain spec mismatch"));
+    }
+}
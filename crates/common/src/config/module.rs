@@ -8,11 +8,11 @@ use crate::{
     commit::client::SignerClient,
     config::{
         constants::{CONFIG_ENV, MODULE_ID_ENV, MODULE_JWT_ENV, SIGNER_URL_ENV},
-        load_env_var,
+        load_chain, load_env_var,
         utils::load_file_from_env,
         BUILDER_PORT_ENV,
     },
-    types::{Chain, Jwt, ModuleId},
+    types::{Chain, ChainLoader, Jwt, ModuleId},
 };
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -80,7 +80,7 @@ pub fn load_commit_module_config<T: DeserializeOwned>() -> Result<StartCommitMod
 
     #[derive(Deserialize, Debug)]
     struct StubConfig<U> {
-        chain: Chain,
+        chain: ChainLoader,
         modules: Vec<ThisModule<U>>,
     }
 
@@ -108,7 +108,7 @@ pub fn load_commit_module_config<T: DeserializeOwned>() -> Result<StartCommitMod
 
     Ok(StartCommitModuleConfig {
         id: module_config.static_config.id,
-        chain: cb_config.chain,
+        chain: load_chain(cb_config.chain)?,
         signer_client,
         extra: module_config.extra,
     })
@@ -149,7 +149,7 @@ pub fn load_builder_module_config<T: DeserializeOwned>() -> eyre::Result<StartBu
 
     #[derive(Deserialize, Debug)]
     struct StubConfig<U> {
-        chain: Chain,
+        chain: ChainLoader,
         modules: Vec<ThisModule<U>>,
     }
 
@@ -175,7 +175,7 @@ pub fn load_builder_module_config<T: DeserializeOwned>() -> eyre::Result<StartBu
 
     Ok(StartBuilderModuleConfig {
         id: module_config.static_config.id,
-        chain: cb_config.chain,
+        chain: load_chain(cb_config.chain)?,
         server_port: builder_events_port,
         extra: module_config.extra,
     })
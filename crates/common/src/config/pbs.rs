@@ -16,8 +16,8 @@ use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use url::Url;
 
 use super::{
-    constants::PBS_IMAGE_DEFAULT, load_optional_env_var, CommitBoostConfig, RuntimeMuxConfig,
-    PBS_ENDPOINT_ENV,
+    constants::PBS_IMAGE_DEFAULT, load_chain, load_optional_env_var, CommitBoostConfig,
+    RuntimeMuxConfig, PBS_ENDPOINT_ENV,
 };
 use crate::{
     commit::client::SignerClient,
@@ -28,7 +28,7 @@ use crate::{
         BuilderEventPublisher, DefaultTimeout, RelayClient, RelayEntry, DEFAULT_PBS_PORT,
         LATE_IN_SLOT_TIME_MS,
     },
-    types::Chain,
+    types::{Chain, ChainLoader},
     utils::{
         as_eth_str, default_bool, default_host, default_u16, default_u256, default_u64, WEI_PER_ETH,
     },
@@ -256,7 +256,7 @@ pub async fn load_pbs_custom_config<T: DeserializeOwned>() -> Result<(PbsModuleC
 
     #[derive(Deserialize, Debug)]
     struct StubConfig<U> {
-        chain: Chain,
+        chain: ChainLoader,
         relays: Vec<RelayConfig>,
         pbs: CustomPbsConfig<U>,
         muxes: Option<PbsMuxes>,
@@ -264,7 +264,8 @@ pub async fn load_pbs_custom_config<T: DeserializeOwned>() -> Result<(PbsModuleC
 
     // load module config including the extra data (if any)
     let cb_config: StubConfig<T> = load_file_from_env(CONFIG_ENV)?;
-    cb_config.pbs.static_config.pbs_config.validate(cb_config.chain).await?;
+    let chain = load_chain(cb_config.chain)?;
+    cb_config.pbs.static_config.pbs_config.validate(chain).await?;
 
     // use endpoint from env if set, otherwise use default host and port
     let endpoint = if let Some(endpoint) = load_optional_env_var(PBS_ENDPOINT_ENV) {
@@ -277,11 +278,9 @@ pub async fn load_pbs_custom_config<T: DeserializeOwned>() -> Result<(PbsModuleC
     };
 
     let muxes = match cb_config.muxes {
-        Some(muxes) => Some(
-            muxes
-                .validate_and_fill(cb_config.chain, &cb_config.pbs.static_config.pbs_config)
-                .await?,
-        ),
+        Some(muxes) => {
+            Some(muxes.validate_and_fill(chain, &cb_config.pbs.static_config.pbs_config).await?)
+        }
         None => None,
     };
 
@@ -319,7 +318,7 @@ pub async fn load_pbs_custom_config<T: DeserializeOwned>() -> Result<(PbsModuleC
 
     Ok((
         PbsModuleConfig {
-            chain: cb_config.chain,
+            chain,
             endpoint,
             pbs_config: Arc::new(cb_config.pbs.static_config.pbs_config),
             relays: relay_clients,
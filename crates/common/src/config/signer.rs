@@ -1,16 +1,26 @@
+use std::path::PathBuf;
+
+use alloy::primitives::B256;
 use bimap::BiHashMap;
-use eyre::{bail, Result};
+use eyre::{bail, ensure, Result};
 use serde::{Deserialize, Serialize};
+use tracing::warn;
 use url::Url;
 
 use super::{
     constants::SIGNER_IMAGE_DEFAULT,
-    utils::{load_env_var, load_jwts},
-    CommitBoostConfig, SIGNER_PORT_ENV,
+    utils::{
+        check_admin_jwt_strength, check_jwts_strength, load_env_var, load_jwts,
+        load_optional_env_var,
+    },
+    CommitBoostConfig, JWTS_FILE_ENV, JWT_MIN_LENGTH_DEFAULT, SIGNER_ADMIN_JWT_ENV,
+    SIGNER_NONCE_FILE_ENV, SIGNER_PORT_ENV, SIGNER_STATS_FILE_ENV, SIGNER_UNIX_SOCKET_ENV,
+    SIGNER_UNIX_SOCKET_MODE_DEFAULT,
 };
 use crate::{
-    signer::{ProxyStore, SignerLoader},
+    signer::{BlsPublicKey, ProxyStore, SignerLoader},
     types::{Chain, Jwt, ModuleId},
+    utils::{default_bool, default_u64},
 };
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -25,6 +35,84 @@ pub enum SignerConfig {
         loader: SignerLoader,
         /// How to store keys
         store: Option<ProxyStore>,
+        /// What the signer does at startup if the configured store can't be
+        /// initialized or its proxies can't be loaded
+        #[serde(default)]
+        store_failure: StoreFailurePolicy,
+        /// If set, only these consensus keys can be used to sign, even if more
+        /// keys are loaded
+        consensus_allowlist: Option<Vec<BlsPublicKey>>,
+        /// Max number of signatures each module can request in a time window
+        signature_quota: Option<SignatureQuota>,
+        /// Max number of consensus keys to load, startup fails if the loader
+        /// finds more
+        max_consensus_keys: Option<usize>,
+        /// Check the withdrawal credentials of the loaded consensus keys on
+        /// a beacon node, at startup and on reload
+        withdrawal_check: Option<WithdrawalCheck>,
+        /// Start even if no consensus key is loaded, e.g. for testing
+        #[serde(default = "default_bool::<false>")]
+        allow_empty_signer: bool,
+        /// Verify every BLS signature before returning it, at the cost of
+        /// extra CPU per request
+        #[serde(default = "default_bool::<false>")]
+        verify_signatures: bool,
+        /// Max time to produce a signature, including waiting for the keys
+        /// to be available. Requests taking longer fail with a timeout
+        sign_timeout_ms: Option<u64>,
+        /// Modules that are refused access, even with a valid JWT
+        module_denylist: Option<Vec<ModuleId>>,
+        /// Modules allowed to sign validator registrations with the consensus
+        /// keys. If not set, no module can
+        registration_modules: Option<Vec<ModuleId>>,
+        /// Modules allowed to request proofs of possession of the consensus
+        /// keys and of their BLS proxies. If not set, no module can
+        pop_modules: Option<Vec<ModuleId>>,
+        /// How often to remove the ERC2335 proxy passwords that have no
+        /// keystore. If not set, they are never removed
+        prune_passwords_interval_secs: Option<u64>,
+        /// Max number of proxies of each scheme returned per consensus key by
+        /// get_pubkeys. If not set, all proxies are returned
+        max_proxies_per_key: Option<usize>,
+        /// Which proxies are returned when there are more than
+        /// `max_proxies_per_key`
+        #[serde(default)]
+        proxy_selection: ProxySelection,
+        /// What get_pubkeys does with proxies whose consensus key is not
+        /// loaded
+        #[serde(default)]
+        orphan_proxies: OrphanProxyPolicy,
+        /// Min length of module JWTs, shorter ones are warned about
+        min_jwt_length: Option<usize>,
+        /// Fail instead of warning when a module JWT is too weak
+        #[serde(default = "default_bool::<false>")]
+        strict_jwts: bool,
+        /// Who can reach the status endpoint
+        #[serde(default)]
+        status_endpoint: StatusEndpoint,
+        /// Serve the admin endpoints, authenticated with the JWT in
+        /// `CB_SIGNER_ADMIN_JWT`. They can export the proxy secret keys, so
+        /// they're disabled unless explicitly enabled
+        #[serde(default = "default_bool::<false>")]
+        enable_admin_api: bool,
+        /// Also serve the API on this unix socket, for modules on the same
+        /// host
+        unix_socket: Option<PathBuf>,
+        /// Permissions of the unix socket, set after binding it
+        unix_socket_mode: Option<u32>,
+        /// Only serve the API on the unix socket
+        #[serde(default = "default_bool::<false>")]
+        disable_tcp: bool,
+        /// Periodically save the signer totals to a file, restored on startup
+        persisted_stats: Option<PersistedStats>,
+        /// Require a nonce higher than the previous one on every sign request
+        /// of a module, the last nonce of each module is persisted in this
+        /// file
+        nonce_file: Option<PathBuf>,
+        /// Also accept unused nonces among the last ones of a module, for
+        /// modules sending concurrent sign requests that can arrive out of
+        /// order. Nonces must strictly increase if not set
+        nonce_window: Option<usize>,
     },
     /// Remote signer module with compatible API
     Remote {
@@ -33,6 +121,96 @@ pub enum SignerConfig {
     },
 }
 
+/// Signature quota applied to each module separately
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct SignatureQuota {
+    /// Max number of signatures in a window
+    pub max_signatures: u64,
+    /// Length of the window in seconds. Windows are fixed, back to back from
+    /// the first request of the module, and the count resets when a new one
+    /// starts
+    pub window_secs: u64,
+}
+
+/// Checks that the loaded consensus keys are validators with the expected
+/// withdrawal credentials, to catch loading the wrong key set
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct WithdrawalCheck {
+    /// Beacon node to fetch the validator records from
+    pub beacon_url: Url,
+    /// Accepted withdrawal credentials
+    pub withdrawal_credentials: Vec<B256>,
+    /// Fail instead of warning when a key has other withdrawal credentials
+    #[serde(default = "default_bool::<false>")]
+    pub strict: bool,
+}
+
+/// Where and how often the signer saves its cumulative stats
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct PersistedStats {
+    /// File the stats are saved to and restored from
+    pub path: PathBuf,
+    /// How often the stats are saved, the last interval is lost on a crash
+    #[serde(default = "default_u64::<60>")]
+    pub interval_secs: u64,
+}
+
+/// Exposure of the signer status endpoint
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum StatusEndpoint {
+    /// Reachable without authentication
+    #[default]
+    Public,
+    /// Requires a valid module JWT
+    Authenticated,
+    /// Not served
+    Disabled,
+}
+
+/// Order in which proxies are picked when their number is capped
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ProxySelection {
+    /// Most recently used first, proxies that never signed last
+    #[default]
+    MostRecent,
+    /// By pubkey
+    Lexicographic,
+}
+
+/// Handling of proxies delegated by a consensus key that is not loaded, e.g.
+/// after the key was removed
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum OrphanProxyPolicy {
+    /// Fail the request
+    #[default]
+    Fail,
+    /// Return them apart from the consensus keys, and track them in a metric
+    Report,
+}
+
+/// Handling of a configured proxy store that fails at startup
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum StoreFailurePolicy {
+    /// Don't start the signer
+    #[default]
+    Fail,
+    /// Start without a store, proxies generated from then on are rejected
+    /// unless ephemeral
+    Continue,
+}
+
+/// Where the signer API is served
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SignerBind {
+    Tcp(u16),
+    Unix { path: PathBuf, mode: u32 },
+}
+
 fn default_signer() -> String {
     SIGNER_IMAGE_DEFAULT.to_string()
 }
@@ -42,8 +220,36 @@ pub struct StartSignerConfig {
     pub chain: Chain,
     pub loader: SignerLoader,
     pub store: Option<ProxyStore>,
+    pub store_failure: StoreFailurePolicy,
+    pub consensus_allowlist: Option<Vec<BlsPublicKey>>,
+    pub signature_quota: Option<SignatureQuota>,
+    pub withdrawal_check: Option<WithdrawalCheck>,
+    pub max_consensus_keys: Option<usize>,
+    pub allow_empty_signer: bool,
+    pub verify_signatures: bool,
+    pub sign_timeout_ms: Option<u64>,
+    pub module_denylist: Option<Vec<ModuleId>>,
+    pub registration_modules: Option<Vec<ModuleId>>,
+    pub pop_modules: Option<Vec<ModuleId>>,
+    pub status_endpoint: StatusEndpoint,
+    pub prune_passwords_interval_secs: Option<u64>,
+    pub max_proxies_per_key: Option<usize>,
+    pub proxy_selection: ProxySelection,
+    pub orphan_proxies: OrphanProxyPolicy,
+    pub min_jwt_length: usize,
+    pub strict_jwts: bool,
+    pub persisted_stats: Option<PersistedStats>,
+    pub nonce_file: Option<PathBuf>,
+    /// Number of recent nonces accepted out of order, 0 if nonces must
+    /// strictly increase
+    pub nonce_window: usize,
     pub server_port: u16,
+    /// Where to serve the API, at least one
+    pub binds: Vec<SignerBind>,
     pub jwts: BiHashMap<ModuleId, Jwt>,
+    /// File the JWTs were loaded from, if any
+    pub jwts_file: Option<PathBuf>,
+    pub admin_jwt: Option<Jwt>,
 }
 
 impl StartSignerConfig {
@@ -51,11 +257,127 @@ impl StartSignerConfig {
         let config = CommitBoostConfig::from_env_path()?;
 
         let jwts = load_jwts()?;
+        let jwts_file = load_optional_env_var(JWTS_FILE_ENV).map(PathBuf::from);
         let server_port = load_env_var(SIGNER_PORT_ENV)?.parse()?;
 
         match config.signer {
-            Some(SignerConfig::Local { loader, store, .. }) => {
-                Ok(StartSignerConfig { chain: config.chain, loader, server_port, jwts, store })
+            Some(SignerConfig::Local {
+                loader,
+                store,
+                store_failure,
+                consensus_allowlist,
+                signature_quota,
+                withdrawal_check,
+                max_consensus_keys,
+                allow_empty_signer,
+                verify_signatures,
+                sign_timeout_ms,
+                module_denylist,
+                registration_modules,
+                pop_modules,
+                status_endpoint,
+                prune_passwords_interval_secs,
+                max_proxies_per_key,
+                proxy_selection,
+                orphan_proxies,
+                min_jwt_length,
+                strict_jwts,
+                enable_admin_api,
+                unix_socket,
+                unix_socket_mode,
+                disable_tcp,
+                persisted_stats,
+                nonce_file,
+                nonce_window,
+                ..
+            }) => {
+                if let Some(quota) = signature_quota {
+                    ensure!(quota.window_secs > 0, "signature quota window must be greater than 0");
+                }
+                ensure!(sign_timeout_ms != Some(0), "sign timeout must be greater than 0");
+                ensure!(
+                    prune_passwords_interval_secs != Some(0),
+                    "password prune interval must be greater than 0"
+                );
+                let mut binds = Vec::new();
+                if !disable_tcp {
+                    binds.push(SignerBind::Tcp(server_port));
+                }
+                if let Some(path) =
+                    load_optional_env_var(SIGNER_UNIX_SOCKET_ENV).map(PathBuf::from).or(unix_socket)
+                {
+                    let mode = unix_socket_mode.unwrap_or(SIGNER_UNIX_SOCKET_MODE_DEFAULT);
+                    ensure!(mode <= 0o777, "unix socket mode must be at most 0o777");
+                    binds.push(SignerBind::Unix { path, mode });
+                }
+                ensure!(!binds.is_empty(), "disable_tcp requires a unix socket to be set");
+                let persisted_stats = persisted_stats
+                    .map(|stats| {
+                        ensure!(
+                            stats.interval_secs > 0,
+                            "stats persist interval must be greater than 0"
+                        );
+                        let path = load_optional_env_var(SIGNER_STATS_FILE_ENV)
+                            .map(PathBuf::from)
+                            .unwrap_or(stats.path);
+                        Ok(PersistedStats { path, ..stats })
+                    })
+                    .transpose()?;
+                let nonce_file = nonce_file.map(|path| {
+                    load_optional_env_var(SIGNER_NONCE_FILE_ENV).map(PathBuf::from).unwrap_or(path)
+                });
+                if nonce_window.is_some() && nonce_file.is_none() {
+                    warn!("nonce_window is set but nonces are disabled without a nonce_file");
+                }
+
+                let min_jwt_length = min_jwt_length.unwrap_or(JWT_MIN_LENGTH_DEFAULT);
+                check_jwts_strength(&jwts, min_jwt_length, strict_jwts)?;
+
+                let admin_jwt = load_optional_env_var(SIGNER_ADMIN_JWT_ENV).map(Jwt);
+                let admin_jwt = if enable_admin_api {
+                    let Some(admin_jwt) = admin_jwt else {
+                        bail!("enable_admin_api requires {SIGNER_ADMIN_JWT_ENV} to be set");
+                    };
+                    check_admin_jwt_strength(&admin_jwt, min_jwt_length, strict_jwts)?;
+                    Some(admin_jwt)
+                } else {
+                    if admin_jwt.is_some() {
+                        warn!("{SIGNER_ADMIN_JWT_ENV} is set but the admin endpoints are disabled");
+                    }
+                    None
+                };
+
+                Ok(StartSignerConfig {
+                    chain: config.chain,
+                    loader,
+                    server_port,
+                    binds,
+                    jwts,
+                    jwts_file,
+                    admin_jwt,
+                    store,
+                    store_failure,
+                    consensus_allowlist,
+                    signature_quota,
+                    withdrawal_check,
+                    max_consensus_keys,
+                    allow_empty_signer,
+                    verify_signatures,
+                    sign_timeout_ms,
+                    module_denylist,
+                    registration_modules,
+                    pop_modules,
+                    status_endpoint,
+                    prune_passwords_interval_secs,
+                    max_proxies_per_key,
+                    proxy_selection,
+                    orphan_proxies,
+                    min_jwt_length,
+                    strict_jwts,
+                    persisted_stats,
+                    nonce_file,
+                    nonce_window: nonce_window.unwrap_or_default(),
+                })
             }
             Some(SignerConfig::Remote { .. }) => bail!("Remote signer configured"),
             None => bail!("Signer config is missing"),
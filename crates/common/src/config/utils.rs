@@ -1,8 +1,14 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
+
 use bimap::BiHashMap;
 use eyre::{bail, Context, Ok, Result};
 use serde::de::DeserializeOwned;
+use tracing::warn;
 
-use super::constants::JWTS_ENV;
+use super::constants::{JWTS_ENV, JWTS_FILE_ENV};
 use crate::types::{Jwt, ModuleId};
 
 pub fn load_env_var(env: &str) -> Result<String> {
@@ -23,24 +29,117 @@ pub fn load_file_from_env<T: DeserializeOwned>(env: &str) -> Result<T> {
     load_from_file(&path)
 }
 
-/// Loads a bidirectional map of module id <-> jwt token from a json env
+/// Loads a bidirectional map of module id <-> jwt token, from the file in
+/// `CB_JWTS_FILE` if set, otherwise from the `CB_JWTS` env
 pub fn load_jwts() -> Result<BiHashMap<ModuleId, Jwt>> {
+    if let Some(path) = load_optional_env_var(JWTS_FILE_ENV) {
+        return load_jwts_from_file(Path::new(&path));
+    }
+
     let jwts = std::env::var(JWTS_ENV).wrap_err(format!("{JWTS_ENV} is not set"))?;
     decode_string_to_map(&jwts)
 }
 
+/// Loads a bidirectional map of module id <-> jwt token from a toml file with
+/// one `module_id = "jwt"` entry per module
+pub fn load_jwts_from_file(path: &Path) -> Result<BiHashMap<ModuleId, Jwt>> {
+    let file = std::fs::read_to_string(path)
+        .wrap_err(format!("Unable to read JWTs file: {}", path.display()))?;
+    // duplicate module ids are already rejected by the toml parser
+    let entries: HashMap<ModuleId, Jwt> =
+        toml::from_str(&file).wrap_err("could not deserialize JWTs file")?;
+
+    let mut jwts = BiHashMap::new();
+    for (module_id, jwt) in entries {
+        insert_jwt(&mut jwts, module_id, jwt)?;
+    }
+
+    Ok(jwts)
+}
+
+/// Inserts the pair in the map, failing instead of silently overwriting an
+/// existing entry with the same module id or jwt
+fn insert_jwt(jwts: &mut BiHashMap<ModuleId, Jwt>, module_id: ModuleId, jwt: Jwt) -> Result<()> {
+    if jwts.contains_left(&module_id) {
+        bail!("Module {module_id} has more than one JWT");
+    }
+    if let Some(other) = jwts.get_by_right(&jwt) {
+        bail!("Modules {other} and {module_id} have the same JWT");
+    }
+
+    jwts.insert(module_id, jwt);
+    Ok(())
+}
+
+/// Checks that every JWT is at least `min_length` long and uses a variety of
+/// characters. Weak JWTs are warned about, or rejected if `strict`. The JWTs
+/// are never logged
+pub fn check_jwts_strength(
+    jwts: &BiHashMap<ModuleId, Jwt>,
+    min_length: usize,
+    strict: bool,
+) -> Result<()> {
+    for (module_id, jwt) in jwts {
+        let Some(reason) = jwt_weakness(jwt, min_length) else {
+            continue;
+        };
+
+        if strict {
+            bail!("JWT of module {module_id} is too weak: {reason}");
+        }
+        warn!(%module_id, "JWT is too weak: {reason}");
+    }
+
+    Ok(())
+}
+
+/// Same check as `check_jwts_strength`, for the admin JWT
+pub fn check_admin_jwt_strength(jwt: &Jwt, min_length: usize, strict: bool) -> Result<()> {
+    let Some(reason) = jwt_weakness(jwt, min_length) else {
+        return Ok(());
+    };
+
+    if strict {
+        bail!("admin JWT is too weak: {reason}");
+    }
+    warn!("Admin JWT is too weak: {reason}");
+
+    Ok(())
+}
+
+fn jwt_weakness(jwt: &Jwt, min_length: usize) -> Option<String> {
+    const MIN_DISTINCT_CHARS: usize = 8;
+
+    let length = jwt.as_str().chars().count();
+    if length < min_length {
+        return Some(format!("{length} characters, at least {min_length} are required"));
+    }
+
+    let distinct = jwt.as_str().chars().collect::<HashSet<_>>().len();
+    if distinct < MIN_DISTINCT_CHARS {
+        return Some(format!(
+            "only {distinct} distinct characters, at least {MIN_DISTINCT_CHARS} are required"
+        ));
+    }
+
+    None
+}
+
 fn decode_string_to_map(raw: &str) -> Result<BiHashMap<ModuleId, Jwt>> {
+    let mut jwts = BiHashMap::new();
+
     // trim the string and split for comma
-    raw.trim()
-        .split(',')
-        .map(|pair| {
-            let mut parts = pair.trim().split('=');
-            match (parts.next(), parts.next()) {
-                (Some(key), Some(value)) => Ok((ModuleId(key.into()), Jwt(value.into()))),
-                _ => bail!("Invalid key-value pair: {pair}"),
+    for pair in raw.trim().split(',') {
+        let mut parts = pair.trim().split('=');
+        match (parts.next(), parts.next()) {
+            (Some(key), Some(value)) => {
+                insert_jwt(&mut jwts, ModuleId(key.into()), Jwt(value.into()))?
             }
-        })
-        .collect()
+            _ => bail!("Invalid key-value pair: {pair}"),
+        }
+    }
+
+    Ok(jwts)
 }
 
 #[cfg(test)]
@@ -56,4 +155,78 @@ mod tests {
         assert_eq!(map.get_by_left(&ModuleId("KEY".into())), Some(&Jwt("VALUE".into())));
         assert_eq!(map.get_by_left(&ModuleId("KEY2".into())), Some(&Jwt("value2".into())));
     }
+
+    #[test]
+    fn test_decode_string_to_map_duplicate_module_id() {
+        let raw = "KEY=VALUE,KEY=value2";
+
+        let err = decode_string_to_map(raw).unwrap_err();
+
+        assert_eq!(err.to_string(), "Module KEY has more than one JWT");
+    }
+
+    #[test]
+    fn test_decode_string_to_map_duplicate_jwt() {
+        let raw = "KEY=VALUE,KEY2=VALUE";
+
+        let err = decode_string_to_map(raw).unwrap_err();
+
+        assert_eq!(err.to_string(), "Modules KEY and KEY2 have the same JWT");
+    }
+
+    #[test]
+    fn test_check_jwts_strength() {
+        let mut jwts = BiHashMap::new();
+        jwts.insert(ModuleId("STRONG".into()), Jwt("8fdhe2Kq0LsP3vXz9Wb1nYtR6uAc4Mgj".into()));
+        assert!(check_jwts_strength(&jwts, 32, true).is_ok());
+
+        jwts.insert(ModuleId("SHORT".into()), Jwt("Kq0LsP3vXz".into()));
+        let err = check_jwts_strength(&jwts, 32, true).unwrap_err().to_string();
+        assert!(err.contains("SHORT") && !err.contains("Kq0LsP3vXz"));
+        // not strict, only warned about
+        assert!(check_jwts_strength(&jwts, 32, false).is_ok());
+    }
+
+    #[test]
+    fn test_check_admin_jwt_strength() {
+        assert!(check_admin_jwt_strength(&Jwt("abcdefgh".repeat(4)), 32, true).is_ok());
+        let err = check_admin_jwt_strength(&Jwt("short".into()), 32, true).unwrap_err();
+        assert!(err.to_string().contains("admin JWT is too weak"));
+        assert!(check_admin_jwt_strength(&Jwt("short".into()), 32, false).is_ok());
+    }
+
+    #[test]
+    fn test_jwt_weakness() {
+        assert!(jwt_weakness(&Jwt("ab".repeat(20)), 32).unwrap().contains("distinct"));
+        assert!(jwt_weakness(&Jwt("abcdefgh".into()), 32).unwrap().contains("8 characters"));
+        assert!(jwt_weakness(&Jwt("abcdefgh".into()), 8).is_none());
+    }
+
+    fn write_jwts_file(name: &str, content: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("cb_{name}_{}.toml", std::process::id()));
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_load_jwts_from_file() {
+        let path = write_jwts_file("jwts", "KEY = \"VALUE\"\nKEY2 = \"value2\"\n");
+
+        let map = load_jwts_from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get_by_left(&ModuleId("KEY".into())), Some(&Jwt("VALUE".into())));
+        assert_eq!(map.get_by_left(&ModuleId("KEY2".into())), Some(&Jwt("value2".into())));
+    }
+
+    #[test]
+    fn test_load_jwts_from_file_duplicate_jwt() {
+        let path = write_jwts_file("jwts_duplicate", "KEY = \"VALUE\"\nKEY2 = \"VALUE\"\n");
+
+        let res = load_jwts_from_file(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(res.unwrap_err().to_string().contains("have the same JWT"));
+    }
 }
@@ -53,9 +53,19 @@ pub fn verify_signed_message<T: TreeHash>(
     msg: &T,
     signature: &BlsSignature,
     domain_mask: [u8; 4],
+) -> Result<(), BlstErrorWrapper> {
+    verify_signed_root(chain, pubkey, msg.tree_hash_root().0, signature, domain_mask)
+}
+
+pub fn verify_signed_root(
+    chain: Chain,
+    pubkey: &BlsPublicKey,
+    object_root: [u8; 32],
+    signature: &BlsSignature,
+    domain_mask: [u8; 4],
 ) -> Result<(), BlstErrorWrapper> {
     let domain = compute_domain(chain, domain_mask);
-    let signing_root = compute_signing_root(msg.tree_hash_root().0, domain);
+    let signing_root = compute_signing_root(object_root, domain);
 
     verify_bls_signature(pubkey, &signing_root, signature)
 }
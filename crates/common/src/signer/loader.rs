@@ -2,7 +2,7 @@ use std::{
     ffi::OsStr,
     fs::{self, File},
     io::BufReader,
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 
 use aes::{
@@ -11,16 +11,20 @@ use aes::{
 };
 use alloy::{primitives::hex::FromHex, rpc::types::beacon::BlsPublicKey};
 use eth2_keystore::{json_keystore::JsonKeystore, Keystore};
-use eyre::{eyre, Context, OptionExt};
+use eyre::{bail, eyre, Context, OptionExt};
 use pbkdf2::{hmac, pbkdf2};
 use serde::{de, Deserialize, Deserializer, Serialize};
+use thiserror::Error;
 use tracing::warn;
 use unicode_normalization::UnicodeNormalization;
 
-use super::{BlsSigner, EcdsaSigner, PrysmDecryptedKeystore, PrysmKeystore};
+use super::{store::encrypt_secret, BlsSigner, EcdsaSigner, PrysmDecryptedKeystore, PrysmKeystore};
 use crate::{
-    config::{load_env_var, SIGNER_DIR_KEYS_ENV, SIGNER_DIR_SECRETS_ENV, SIGNER_KEYS_ENV},
-    signer::ConsensusSigner,
+    config::{
+        load_env_var, load_optional_env_var, SIGNER_DIR_KEYS_ENV, SIGNER_DIR_SECRETS_ENV,
+        SIGNER_KEYS_ENV,
+    },
+    signer::{BlsPublicKey as ConsensusPublicKey, ConsensusSigner},
 };
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -95,6 +99,182 @@ impl SignerLoader {
     }
 }
 
+#[derive(Debug, Error)]
+pub enum ReencryptError {
+    #[error("keys loaded from {0} can't be re-encrypted")]
+    Unsupported(&'static str),
+    #[error("wrong password for keystore {0:?}")]
+    WrongPassword(PathBuf),
+    #[error(
+        "{0:?} are not loaded, they share the password file and would no longer decrypt with \
+         the new password"
+    )]
+    NotLoaded(Vec<PathBuf>),
+    #[error("{err}, these files were already rewritten: {rewritten:?}")]
+    Partial { rewritten: Vec<PathBuf>, err: eyre::Report },
+    #[error("{0}")]
+    Other(eyre::Report),
+}
+
+impl From<eyre::Report> for ReencryptError {
+    fn from(err: eyre::Report) -> Self {
+        ReencryptError::Other(err)
+    }
+}
+
+/// Files rewritten by a re-encryption
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct ReencryptedKeystores {
+    pub keystores: Vec<PathBuf>,
+    pub password_file: PathBuf,
+}
+
+impl SignerLoader {
+    /// Re-encrypts the keystores of the loaded consensus keys with a new
+    /// password, and writes the new password to the password file. Only
+    /// layouts with a single password file are supported, so the keystores and
+    /// their password can be changed together.
+    ///
+    /// All keystores are decrypted and all new files written next to the old
+    /// ones before any is replaced, so nothing changes if the old password is
+    /// wrong or a write fails. If replacing a file fails, the files already
+    /// replaced are returned in the error
+    pub fn reencrypt_keystores(
+        &self,
+        loaded: &[ConsensusPublicKey],
+        old_password: &[u8],
+        new_password: &[u8],
+    ) -> Result<ReencryptedKeystores, ReencryptError> {
+        let (keys_path, password_file) = match self {
+            SignerLoader::File { .. } => return Err(ReencryptError::Unsupported("a plain file")),
+            SignerLoader::ValidatorsDir { format: ValidatorKeysFormat::Prysm, .. } => {
+                return Err(ReencryptError::Unsupported("a Prysm wallet"))
+            }
+            SignerLoader::ValidatorsDir { format: ValidatorKeysFormat::Lighthouse, .. } => {
+                return Err(ReencryptError::Unsupported(
+                    "a Lighthouse validators dir, which has a password file per key",
+                ))
+            }
+            SignerLoader::ValidatorsDir { format: ValidatorKeysFormat::Teku, .. } => {
+                return Err(ReencryptError::Unsupported(
+                    "a Teku validators dir, which has a password file per key",
+                ))
+            }
+            SignerLoader::ValidatorsDir {
+                keys_path,
+                secrets_path,
+                format: ValidatorKeysFormat::Lodestar,
+            } => {
+                let keys_path = load_optional_env_var(SIGNER_DIR_KEYS_ENV)
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|| keys_path.clone());
+                let password_file = load_optional_env_var(SIGNER_DIR_SECRETS_ENV)
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|| secrets_path.clone());
+                (keys_path, password_file)
+            }
+        };
+
+        let mut keystores = Vec::new();
+        let mut not_loaded = Vec::new();
+        for path in keystore_paths(&keys_path)? {
+            let keystore = read_keystore(&path)?;
+            let secret = eth2_keystore::decrypt(old_password, &keystore.crypto)
+                .map_err(|_| ReencryptError::WrongPassword(path.clone()))?;
+            let pubkey = ConsensusSigner::new_from_bytes(secret.as_bytes())?.pubkey();
+            if loaded.contains(&pubkey) {
+                keystores.push((path, keystore, secret));
+            } else {
+                not_loaded.push(path);
+            }
+        }
+        if !not_loaded.is_empty() {
+            return Err(ReencryptError::NotLoaded(not_loaded));
+        }
+
+        let mut staged = Vec::with_capacity(keystores.len() + 1);
+        let staging = (|| -> eyre::Result<()> {
+            for (path, mut keystore, secret) in keystores {
+                keystore.crypto = encrypt_secret(secret.as_bytes(), new_password)?;
+
+                let decrypted = eth2_keystore::decrypt(new_password, &keystore.crypto)
+                    .map_err(|_| eyre!("failed to decrypt re-encrypted keystore {path:?}"))?;
+                if decrypted.as_bytes() != secret.as_bytes() {
+                    bail!("re-encrypted keystore {path:?} has a different key");
+                }
+
+                let tmp_path = tmp_path(&path);
+                fs::write(&tmp_path, serde_json::to_string(&keystore)?)
+                    .wrap_err_with(|| format!("failed to write {tmp_path:?}"))?;
+                staged.push((tmp_path, path));
+            }
+
+            // replaced last, so the old password still works until every
+            // keystore is replaced
+            let tmp_path = tmp_path(&password_file);
+            fs::write(&tmp_path, new_password)
+                .wrap_err_with(|| format!("failed to write {tmp_path:?}"))?;
+            staged.push((tmp_path, password_file.clone()));
+
+            Ok(())
+        })();
+        if let Err(err) = staging {
+            remove_staged(&staged);
+            return Err(err.into());
+        }
+
+        let mut rewritten = Vec::with_capacity(staged.len());
+        for (i, (tmp_path, path)) in staged.iter().enumerate() {
+            if let Err(err) = fs::rename(tmp_path, path) {
+                remove_staged(&staged[i..]);
+                let err = eyre!(err).wrap_err(format!("failed to replace {path:?}"));
+                return Err(ReencryptError::Partial { rewritten, err });
+            }
+            rewritten.push(path.clone());
+        }
+
+        rewritten.pop();
+        Ok(ReencryptedKeystores { keystores: rewritten, password_file })
+    }
+}
+
+fn tmp_path(path: &Path) -> PathBuf {
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    PathBuf::from(tmp_path)
+}
+
+/// Removes the staged files that were not moved in place, best effort
+fn remove_staged(staged: &[(PathBuf, PathBuf)]) {
+    for (tmp_path, _) in staged {
+        if let Err(err) = fs::remove_file(tmp_path) {
+            warn!(%err, ?tmp_path, "Failed to remove staged keystore file");
+        }
+    }
+}
+
+/// Keystore files in a Lodestar keys directory, skipping the files left
+/// staged by an interrupted re-encryption
+fn keystore_paths(keys_path: &Path) -> eyre::Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+
+    for entry in fs::read_dir(keys_path)? {
+        let path = entry?.path();
+        if path.is_file() && path.extension().is_none_or(|ext| ext != "tmp") {
+            paths.push(path);
+        }
+    }
+
+    Ok(paths)
+}
+
+fn read_keystore(path: &Path) -> eyre::Result<JsonKeystore> {
+    let file = File::open(path).wrap_err_with(|| format!("failed to open keystore {path:?}"))?;
+    let keystore = serde_json::from_reader(BufReader::new(file))
+        .wrap_err_with(|| format!("invalid keystore {path:?}"))?;
+    Ok(keystore)
+}
+
 pub struct FileKey {
     pub secret_key: [u8; 32],
 }
@@ -311,7 +491,10 @@ mod tests {
 
     use super::{load_from_lighthouse_format, load_from_lodestar_format, FileKey};
     use crate::signer::{
-        loader::{load_from_prysm_format, load_from_teku_format},
+        loader::{
+            load_from_prysm_format, load_from_teku_format, ReencryptError, ReencryptedKeystores,
+            SignerLoader, ValidatorKeysFormat,
+        },
         BlsPublicKey, BlsSigner,
     };
 
@@ -407,4 +590,80 @@ mod tests {
             hex!("b3a22e4a673ac7a153ab5b3c17a4dbef55f7e47210b20c0cbb0e66df5b36bb49ef808577610b034172e955d2312a61b9")
         )));
     }
+
+    #[test]
+    fn test_reencrypt_keystores() {
+        let pubkey = "0x883827193f7627cd04e621e1e8d56498362a52b2a30c9a1c72036eb935c4278dee23d38a24d2f7dda62689886f0c39f4";
+        let tmp_path = std::env::temp_dir().join("test_reencrypt_keystores");
+        let _ = std::fs::remove_dir_all(&tmp_path);
+        let keys_path = tmp_path.join("keys");
+        std::fs::create_dir_all(&keys_path).unwrap();
+        std::fs::copy(
+            format!("../../tests/data/keystores/teku-keys/{pubkey}.json"),
+            keys_path.join(format!("{pubkey}.json")),
+        )
+        .unwrap();
+
+        let old_password =
+            std::fs::read(format!("../../tests/data/keystores/secrets/{pubkey}")).unwrap();
+        let password_path = tmp_path.join("password");
+        std::fs::write(&password_path, &old_password).unwrap();
+
+        let loader = SignerLoader::ValidatorsDir {
+            keys_path: keys_path.clone(),
+            secrets_path: password_path.clone(),
+            format: ValidatorKeysFormat::Lodestar,
+        };
+
+        let loaded = load_from_lodestar_format(
+            keys_path.to_string_lossy().to_string(),
+            format!("../../tests/data/keystores/secrets/{pubkey}"),
+        )
+        .unwrap()
+        .iter()
+        .map(|signer| signer.pubkey())
+        .collect::<Vec<_>>();
+
+        let err =
+            loader.reencrypt_keystores(&loaded, b"wrong password", b"new password").unwrap_err();
+        assert!(matches!(err, ReencryptError::WrongPassword(_)));
+
+        // keystores that aren't loaded would be stranded by the new password
+        let err = loader.reencrypt_keystores(&[], &old_password, b"new password").unwrap_err();
+        assert!(matches!(err, ReencryptError::NotLoaded(_)));
+
+        // files staged by an interrupted re-encryption are not keystores
+        let stale_path = keys_path.join("stale.json.tmp");
+        std::fs::write(&stale_path, "{}").unwrap();
+
+        let rewritten =
+            loader.reencrypt_keystores(&loaded, &old_password, b"new password").unwrap();
+        std::fs::remove_file(&stale_path).unwrap();
+        assert_eq!(rewritten, ReencryptedKeystores {
+            keystores: vec![keys_path.join(format!("{pubkey}.json"))],
+            password_file: password_path.clone(),
+        });
+        assert_eq!(std::fs::read(&password_path).unwrap(), b"new password");
+        assert_eq!(std::fs::read_dir(&keys_path).unwrap().count(), 1);
+
+        let signers = load_from_lodestar_format(
+            keys_path.to_string_lossy().to_string(),
+            password_path.to_string_lossy().to_string(),
+        )
+        .unwrap();
+        assert_eq!(signers.len(), 1);
+        assert_eq!(signers[0].pubkey().to_string(), pubkey);
+    }
+
+    #[test]
+    fn test_reencrypt_refuses_per_key_passwords() {
+        let loader = SignerLoader::ValidatorsDir {
+            keys_path: "../../tests/data/keystores/keys".into(),
+            secrets_path: "../../tests/data/keystores/secrets".into(),
+            format: ValidatorKeysFormat::Lighthouse,
+        };
+
+        let err = loader.reencrypt_keystores(&[], b"old password", b"new password").unwrap_err();
+        assert!(matches!(err, ReencryptError::Unsupported(_)));
+    }
 }
@@ -7,12 +7,18 @@ use tree_hash::TreeHash;
 use tree_hash_derive::TreeHash;
 
 use crate::{
-    error::BlstErrorWrapper, signature::sign_commit_boost_root, types::Chain,
+    error::BlstErrorWrapper,
+    signature::{sign_builder_message, sign_commit_boost_root},
+    types::Chain,
     utils::blst_pubkey_to_alloy,
 };
 
 pub type BlsSecretKey = blst::min_pk::SecretKey;
 
+/// Domain separation tag of proofs of possession, from the proof of possession
+/// scheme of the IETF BLS signatures draft
+pub const BLS_DST_POP: &[u8] = b"BLS_POP_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
 // TODO(David):
 // This wrapper type is potentially a temporary solution, merely to implement
 // `TreeHash`. Remove when progress is made on this issue (https://github.com/sigp/tree_hash/issues/22)
@@ -72,6 +78,25 @@ impl BlsSigner {
     pub async fn sign_msg(&self, chain: Chain, msg: &impl TreeHash) -> BlsSignature {
         self.sign(chain, msg.tree_hash_root().0).await
     }
+
+    /// Signs with the application builder domain instead of the commit-boost
+    /// one, e.g. for validator registrations
+    pub async fn sign_builder_msg(&self, chain: Chain, msg: &impl TreeHash) -> BlsSignature {
+        match self {
+            BlsSigner::Local(sk) => sign_builder_message(chain, sk, msg),
+        }
+    }
+
+    /// Signs the pubkey with the proof of possession DST, proving the key is
+    /// held without signing anything that could be used in another domain
+    pub async fn proof_of_possession(&self) -> BlsSignature {
+        match self {
+            BlsSigner::Local(sk) => {
+                let signature = sk.sign(self.pubkey().as_slice(), BLS_DST_POP, &[]).to_bytes();
+                BlsSignature::from_slice(&signature)
+            }
+        }
+    }
 }
 
 pub fn random_secret() -> BlsSecretKey {
@@ -92,13 +117,29 @@ pub fn verify_bls_signature(
     pubkey: &BlsPublicKeyInner,
     msg: &[u8],
     signature: &BlsSignature,
+) -> Result<(), BlstErrorWrapper> {
+    verify_with_dst(pubkey, msg, signature, BLS_DST_SIG)
+}
+
+pub fn verify_proof_of_possession(
+    pubkey: &BlsPublicKeyInner,
+    signature: &BlsSignature,
+) -> Result<(), BlstErrorWrapper> {
+    verify_with_dst(pubkey, pubkey.as_slice(), signature, BLS_DST_POP)
+}
+
+fn verify_with_dst(
+    pubkey: &BlsPublicKeyInner,
+    msg: &[u8],
+    signature: &BlsSignature,
+    dst: &[u8],
 ) -> Result<(), BlstErrorWrapper> {
     use crate::utils::{alloy_pubkey_to_blst, alloy_sig_to_blst};
 
     let pubkey = alloy_pubkey_to_blst(pubkey)?;
     let signature = alloy_sig_to_blst(signature)?;
 
-    let res = signature.verify(true, msg, BLS_DST_SIG, &[], &pubkey, true);
+    let res = signature.verify(true, msg, dst, &[], &pubkey, true);
     if res == BLST_ERROR::BLST_SUCCESS {
         Ok(())
     } else {
@@ -34,7 +34,7 @@ pub struct EcdsaPublicKey {
 impl EcdsaPublicKey {
     /// Size of the public key in bytes. We store the SEC1 encoded affine point
     /// compressed, thus 33 bytes.
-    const SIZE: usize = 33;
+    pub const SIZE: usize = 33;
 }
 
 impl Default for EcdsaPublicKey {
@@ -4,6 +4,7 @@ use std::{
     io::Write,
     path::{Path, PathBuf},
     str::FromStr,
+    time::{Duration, SystemTime},
 };
 
 use alloy::{
@@ -19,34 +20,65 @@ use eth2_keystore::{
     },
     Uuid, IV_SIZE, SALT_SIZE,
 };
-use eyre::OptionExt;
+use eyre::{bail, ensure, eyre, OptionExt};
 use rand::Rng;
 use serde::{Deserialize, Serialize};
-use tracing::warn;
+use tracing::{error, info, warn};
 
 use super::{load_bls_signer, load_ecdsa_signer};
 use crate::{
-    commit::request::{EncryptionScheme, ProxyDelegation, PublicKey, SignedProxyDelegation},
-    config::{load_env_var, PROXY_DIR_ENV, PROXY_DIR_KEYS_ENV, PROXY_DIR_SECRETS_ENV},
+    commit::request::{
+        DelegationVersion, EncryptionScheme, ProxyDelegation, PublicKey, SignedProxyDelegation,
+    },
+    config::{
+        load_env_var, PROXY_DIR_ENV, PROXY_DIR_KEYS_ENV, PROXY_DIR_SECRETS_ENV,
+        PROXY_STORE_PASSWORD_ENV,
+    },
     signer::{
         BlsProxySigner, BlsPublicKey, BlsSigner, EcdsaProxySigner, EcdsaPublicKey, EcdsaSigner,
         ProxySigners,
     },
     types::ModuleId,
+    utils::default_bool,
 };
 
 #[derive(Debug, Serialize, Deserialize)]
 struct KeyAndDelegation<T: PublicKey> {
     secret: Bytes,
     delegation: SignedProxyDelegation<T>,
+    /// Missing for proxies stored before the creation time was recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    created_at: Option<u64>,
+}
+
+/// Proxy of an encrypted file store, the secret is encrypted as in an EIP-2335
+/// keystore
+#[derive(Serialize, Deserialize)]
+struct EncryptedKeyAndDelegation<T: PublicKey> {
+    crypto: Crypto,
+    delegation: SignedProxyDelegation<T>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    created_at: Option<u64>,
+}
+
+/// A proxy of the file store in either format, plaintext proxies can be found
+/// in a store that was encrypted later
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredProxy<T: PublicKey> {
+    Encrypted(EncryptedKeyAndDelegation<T>),
+    Plain(KeyAndDelegation<T>),
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 #[serde(untagged)]
 pub enum ProxyStore {
-    /// Stores private keys in plaintext to a file, do not use in prod
+    /// Stores private keys in a file, in plaintext unless encrypted with the
+    /// password in `CB_PROXY_STORE_PASSWORD`
     File {
         proxy_dir: PathBuf,
+        #[serde(default = "default_bool::<false>")]
+        encrypted: bool,
     },
     ERC2335 {
         keys_path: PathBuf,
@@ -57,10 +89,10 @@ pub enum ProxyStore {
 impl ProxyStore {
     pub fn init_from_env(self) -> eyre::Result<Self> {
         Ok(match self {
-            ProxyStore::File { proxy_dir } => {
+            ProxyStore::File { proxy_dir, encrypted } => {
                 let path = load_env_var(PROXY_DIR_ENV)
                     .unwrap_or(proxy_dir.to_str().ok_or_eyre("Missing proxy dir")?.to_string());
-                ProxyStore::File { proxy_dir: PathBuf::from(path) }
+                ProxyStore::File { proxy_dir: PathBuf::from(path), encrypted }
             }
             ProxyStore::ERC2335 { keys_path, secrets_path } => {
                 let keys_path = if let Ok(path) = load_env_var(PROXY_DIR_KEYS_ENV) {
@@ -79,32 +111,45 @@ impl ProxyStore {
         })
     }
 
+    /// Password of an encrypted file store. It's loaded from the env when
+    /// needed, so it's never part of the config
+    fn file_store_password(&self) -> eyre::Result<Option<String>> {
+        let ProxyStore::File { encrypted: true, .. } = self else {
+            return Ok(None);
+        };
+
+        let password = load_env_var(PROXY_STORE_PASSWORD_ENV)?;
+        ensure!(!password.is_empty(), "{PROXY_STORE_PASSWORD_ENV} must not be empty");
+
+        Ok(Some(password))
+    }
+
     pub fn store_proxy_bls(
         &self,
         module_id: &ModuleId,
         proxy: &BlsProxySigner,
     ) -> eyre::Result<()> {
         match self {
-            ProxyStore::File { proxy_dir } => {
+            ProxyStore::File { proxy_dir, .. } => {
                 let file_path = proxy_dir
                     .join(module_id.to_string())
                     .join("bls")
                     .join(proxy.signer.pubkey().to_string());
                 let secret = Bytes::from(proxy.signer.secret());
-                let to_store = KeyAndDelegation { secret, delegation: proxy.delegation };
-                let content = serde_json::to_vec(&to_store)?;
-
-                if let Some(parent) = file_path.parent() {
-                    create_dir_all(parent)?;
-                }
-
-                let mut file = std::fs::File::create(file_path)?;
-                file.write_all(content.as_ref())?;
+                let password = self.file_store_password()?;
+                write_file_proxy(
+                    &file_path,
+                    secret,
+                    proxy.delegation,
+                    proxy.created_at,
+                    password.as_deref(),
+                )?;
             }
             ProxyStore::ERC2335 { keys_path, secrets_path } => {
                 store_erc2335_key(
                     module_id,
                     proxy.delegation,
+                    proxy.created_at,
                     proxy.secret().to_vec(),
                     keys_path,
                     secrets_path,
@@ -122,26 +167,26 @@ impl ProxyStore {
         proxy: &EcdsaProxySigner,
     ) -> eyre::Result<()> {
         match self {
-            ProxyStore::File { proxy_dir } => {
+            ProxyStore::File { proxy_dir, .. } => {
                 let file_path = proxy_dir
                     .join(module_id.to_string())
                     .join("ecdsa")
                     .join(proxy.signer.pubkey().to_string());
                 let secret = Bytes::from(proxy.signer.secret());
-                let to_store = KeyAndDelegation { secret, delegation: proxy.delegation };
-                let content = serde_json::to_vec(&to_store)?;
-
-                if let Some(parent) = file_path.parent() {
-                    create_dir_all(parent)?;
-                }
-
-                let mut file = std::fs::File::create(file_path)?;
-                file.write_all(content.as_ref())?;
+                let password = self.file_store_password()?;
+                write_file_proxy(
+                    &file_path,
+                    secret,
+                    proxy.delegation,
+                    proxy.created_at,
+                    password.as_deref(),
+                )?;
             }
             ProxyStore::ERC2335 { keys_path, secrets_path } => {
                 store_erc2335_key(
                     module_id,
                     proxy.delegation,
+                    proxy.created_at,
                     proxy.secret(),
                     keys_path,
                     secrets_path,
@@ -153,6 +198,108 @@ impl ProxyStore {
         Ok(())
     }
 
+    /// Removes a stored proxy key, together with its delegation and password
+    pub fn delete_proxy<T: PublicKey>(
+        &self,
+        module_id: &ModuleId,
+        delegation: &ProxyDelegation<T>,
+        scheme: EncryptionScheme,
+    ) -> eyre::Result<()> {
+        let proxy_pubkey = delegation.proxy.to_string();
+
+        match self {
+            ProxyStore::File { proxy_dir, .. } => {
+                remove_file_if_exists(
+                    &proxy_dir
+                        .join(module_id.to_string())
+                        .join(scheme.to_string())
+                        .join(proxy_pubkey),
+                )?;
+            }
+            ProxyStore::ERC2335 { keys_path, secrets_path } => {
+                let keys_dir = keys_path
+                    .join(delegation.delegator.to_string())
+                    .join(&module_id.0)
+                    .join(scheme.to_string());
+                remove_file_if_exists(&keys_dir.join(format!("{proxy_pubkey}.json")))?;
+                remove_file_if_exists(&keys_dir.join(format!("{proxy_pubkey}.sig")))?;
+                remove_file_if_exists(&keys_dir.join(format!("{proxy_pubkey}.version")))?;
+                remove_file_if_exists(&keys_dir.join(format!("{proxy_pubkey}.created")))?;
+                remove_file_if_exists(
+                    &secrets_path
+                        .join(delegation.delegator.to_string())
+                        .join(&module_id.0)
+                        .join(scheme.to_string())
+                        .join(proxy_pubkey),
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Removes the files left under the previous delegator of a proxy, once
+    /// it was stored again with a new delegation
+    pub fn delete_stale_delegation<T: PublicKey>(
+        &self,
+        module_id: &ModuleId,
+        old_delegation: &ProxyDelegation<T>,
+        scheme: EncryptionScheme,
+    ) -> eyre::Result<()> {
+        match self {
+            // the path doesn't depend on the delegator, storing the proxy already
+            // replaced the old delegation
+            ProxyStore::File { .. } => Ok(()),
+            ProxyStore::ERC2335 { .. } => self.delete_proxy(module_id, old_delegation, scheme),
+        }
+    }
+
+    /// Removes the ERC2335 password files with no matching keystore, e.g. left
+    /// by a proxy generation that failed after writing the password. Files
+    /// modified less than `grace_period` ago are kept, as their keystore may
+    /// still be being written. Returns the removed files
+    pub fn prune_orphan_passwords(&self, grace_period: Duration) -> eyre::Result<Vec<PathBuf>> {
+        let ProxyStore::ERC2335 { keys_path, secrets_path } = self else {
+            return Ok(vec![]);
+        };
+
+        let mut pruned = Vec::new();
+        let now = SystemTime::now();
+
+        // secrets are stored as {delegator}/{module_id}/{scheme}/{proxy}
+        for delegator_dir in subdirs(secrets_path)? {
+            for module_dir in subdirs(&delegator_dir)? {
+                for scheme_dir in subdirs(&module_dir)? {
+                    for entry in std::fs::read_dir(&scheme_dir)? {
+                        let pass_path = entry?.path();
+                        if !pass_path.is_file() {
+                            continue;
+                        }
+
+                        let Ok(relative) = pass_path.strip_prefix(secrets_path) else {
+                            continue;
+                        };
+                        let mut json_path = keys_path.join(relative);
+                        json_path.set_extension("json");
+                        if json_path.exists() {
+                            continue;
+                        }
+
+                        let modified = std::fs::metadata(&pass_path)?.modified()?;
+                        if now.duration_since(modified).unwrap_or_default() < grace_period {
+                            continue;
+                        }
+
+                        std::fs::remove_file(&pass_path)?;
+                        pruned.push(pass_path);
+                    }
+                }
+            }
+        }
+
+        Ok(pruned)
+    }
+
     #[allow(clippy::type_complexity)]
     pub fn load_proxies(
         &self,
@@ -162,7 +309,9 @@ impl ProxyStore {
         HashMap<ModuleId, Vec<EcdsaPublicKey>>,
     )> {
         match self {
-            ProxyStore::File { proxy_dir } => {
+            ProxyStore::File { proxy_dir, .. } => {
+                let password = self.file_store_password()?;
+
                 // HashMaps to store module_id -> content mappings
                 let mut proxy_signers = ProxySigners::default();
                 let mut bls_map: HashMap<ModuleId, Vec<BlsPublicKey>> = HashMap::new();
@@ -190,17 +339,13 @@ impl ProxyStore {
                                     let entry = entry?;
                                     let path = entry.path();
 
-                                    if path.is_file() {
-                                        let file_content = read_to_string(&path)?;
-                                        let key_and_delegation: KeyAndDelegation<BlsPublicKey> =
-                                            serde_json::from_str(&file_content)?;
-                                        let signer =
-                                            BlsSigner::new_from_bytes(&key_and_delegation.secret)?;
+                                    if is_file_proxy(&path) {
+                                        let (secret, delegation, created_at) =
+                                            read_file_proxy(&path, password.as_deref())?;
+                                        let signer = BlsSigner::new_from_bytes(&secret)?;
                                         let pubkey = signer.pubkey();
-                                        let proxy_signer = BlsProxySigner {
-                                            signer,
-                                            delegation: key_and_delegation.delegation,
-                                        };
+                                        let proxy_signer =
+                                            BlsProxySigner { signer, delegation, created_at };
 
                                         proxy_signers.bls_signers.insert(pubkey, proxy_signer);
                                         bls_map.entry(module_id.clone()).or_default().push(pubkey);
@@ -214,18 +359,13 @@ impl ProxyStore {
                                     let entry = entry?;
                                     let path = entry.path();
 
-                                    if path.is_file() {
-                                        let file_content = read_to_string(&path)?;
-                                        let key_and_delegation: KeyAndDelegation<EcdsaPublicKey> =
-                                            serde_json::from_str(&file_content)?;
-                                        let signer = EcdsaSigner::new_from_bytes(
-                                            &key_and_delegation.secret,
-                                        )?;
+                                    if is_file_proxy(&path) {
+                                        let (secret, delegation, created_at) =
+                                            read_file_proxy(&path, password.as_deref())?;
+                                        let signer = EcdsaSigner::new_from_bytes(&secret)?;
                                         let pubkey = signer.pubkey();
-                                        let proxy_signer = EcdsaProxySigner {
-                                            signer,
-                                            delegation: key_and_delegation.delegation,
-                                        };
+                                        let proxy_signer =
+                                            EcdsaProxySigner { signer, delegation, created_at };
 
                                         proxy_signers.ecdsa_signers.insert(pubkey, proxy_signer);
                                         ecdsa_map
@@ -309,6 +449,24 @@ impl ProxyStore {
                                         continue;
                                     }
                                 };
+                                let version = match read_erc2335_version(
+                                    &bls_path.join(format!("{name}.version")),
+                                ) {
+                                    Ok(version) => version,
+                                    Err(e) => {
+                                        warn!("Failed to read delegation version: {e}");
+                                        continue;
+                                    }
+                                };
+                                let created_at = match read_erc2335_created_at(
+                                    &bls_path.join(format!("{name}.created")),
+                                ) {
+                                    Ok(created_at) => created_at,
+                                    Err(e) => {
+                                        warn!("Failed to read proxy creation time: {e}");
+                                        continue;
+                                    }
+                                };
 
                                 let proxy_signer = BlsProxySigner {
                                     signer: signer.clone(),
@@ -318,7 +476,9 @@ impl ProxyStore {
                                             proxy: signer.pubkey(),
                                         },
                                         signature: delegation_signature,
+                                        version,
                                     },
+                                    created_at,
                                 };
 
                                 proxy_signers.bls_signers.insert(signer.pubkey(), proxy_signer);
@@ -363,6 +523,24 @@ impl ProxyStore {
                                         continue;
                                     }
                                 };
+                                let version = match read_erc2335_version(
+                                    &ecdsa_path.join(format!("{name}.version")),
+                                ) {
+                                    Ok(version) => version,
+                                    Err(e) => {
+                                        warn!("Failed to read delegation version: {e}");
+                                        continue;
+                                    }
+                                };
+                                let created_at = match read_erc2335_created_at(
+                                    &ecdsa_path.join(format!("{name}.created")),
+                                ) {
+                                    Ok(created_at) => created_at,
+                                    Err(e) => {
+                                        warn!("Failed to read proxy creation time: {e}");
+                                        continue;
+                                    }
+                                };
 
                                 let proxy_signer = EcdsaProxySigner {
                                     signer: signer.clone(),
@@ -372,7 +550,9 @@ impl ProxyStore {
                                             proxy: signer.pubkey(),
                                         },
                                         signature: delegation_signature,
+                                        version,
                                     },
+                                    created_at,
                                 };
 
                                 proxy_signers.ecdsa_signers.insert(signer.pubkey(), proxy_signer);
@@ -390,13 +570,199 @@ impl ProxyStore {
     }
 }
 
+fn remove_file_if_exists(path: &Path) -> eyre::Result<()> {
+    match std::fs::remove_file(path) {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Reads the version stored next to the delegation signature of an ERC2335
+/// proxy, delegations stored without one are V1
+fn read_erc2335_version(path: &Path) -> eyre::Result<DelegationVersion> {
+    match std::fs::read_to_string(path) {
+        Ok(version) => DelegationVersion::try_from(version.trim().parse::<u64>()?),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(DelegationVersion::V1),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Reads the creation time stored next to an ERC2335 proxy, none for proxies
+/// stored before it was recorded
+fn read_erc2335_created_at(path: &Path) -> eyre::Result<Option<u64>> {
+    match std::fs::read_to_string(path) {
+        Ok(created_at) => Ok(Some(created_at.trim().parse()?)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Directories directly under `path`, none if `path` doesn't exist
+fn subdirs(path: &Path) -> eyre::Result<Vec<PathBuf>> {
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut dirs = Vec::new();
+    for entry in std::fs::read_dir(path)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            dirs.push(path);
+        }
+    }
+
+    Ok(dirs)
+}
+
+/// Whether a file of the file store is a proxy, and not a temporary file left
+/// by an interrupted write
+fn is_file_proxy(path: &Path) -> bool {
+    path.is_file() && path.extension().is_none_or(|ext| ext != "tmp")
+}
+
+/// Writes a proxy of the file store, encrypting its secret if a password is
+/// set. The file is replaced atomically, so a proxy encrypted in place is never
+/// lost
+fn write_file_proxy<T: PublicKey>(
+    path: &Path,
+    secret: Bytes,
+    delegation: SignedProxyDelegation<T>,
+    created_at: Option<u64>,
+    password: Option<&str>,
+) -> eyre::Result<()> {
+    let content = match password {
+        Some(password) => {
+            let crypto = encrypt_secret(&secret, password.as_bytes())?;
+            serde_json::to_vec(&EncryptedKeyAndDelegation { crypto, delegation, created_at })?
+        }
+        None => serde_json::to_vec(&KeyAndDelegation { secret, delegation, created_at })?,
+    };
+
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent)?;
+    }
+
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+
+    let mut file = std::fs::File::create(&tmp_path)?;
+    file.write_all(&content)?;
+    std::fs::rename(&tmp_path, path)?;
+
+    Ok(())
+}
+
+/// Reads a proxy of the file store. If the store is encrypted, plaintext
+/// proxies are encrypted in place, to migrate stores created before the
+/// encryption was enabled
+fn read_file_proxy<T: PublicKey>(
+    path: &Path,
+    password: Option<&str>,
+) -> eyre::Result<(Bytes, SignedProxyDelegation<T>, Option<u64>)> {
+    let stored: StoredProxy<T> = serde_json::from_str(&read_to_string(path)?)?;
+
+    match (stored, password) {
+        (StoredProxy::Plain(proxy), None) => Ok((proxy.secret, proxy.delegation, proxy.created_at)),
+        (StoredProxy::Plain(proxy), Some(password)) => {
+            write_file_proxy(
+                path,
+                proxy.secret.clone(),
+                proxy.delegation,
+                proxy.created_at,
+                Some(password),
+            )?;
+            info!(?path, "Encrypted plaintext proxy");
+            Ok((proxy.secret, proxy.delegation, proxy.created_at))
+        }
+        (StoredProxy::Encrypted(proxy), Some(password)) => {
+            let secret = eth2_keystore::decrypt(password.as_bytes(), &proxy.crypto)
+                .map_err(|_| eyre!("failed to decrypt {path:?}, wrong proxy store password"))?;
+            Ok((Bytes::copy_from_slice(secret.as_bytes()), proxy.delegation, proxy.created_at))
+        }
+        (StoredProxy::Encrypted(_), None) => {
+            bail!("{path:?} is encrypted, but the proxy store is not configured as encrypted")
+        }
+    }
+}
+
+/// Encrypts a secret key with a new random salt and IV, using the default KDF
+pub fn encrypt_secret(secret: &[u8], password: &[u8]) -> eyre::Result<Crypto> {
+    let salt: [u8; SALT_SIZE] = rand::thread_rng().gen();
+    let iv: [u8; IV_SIZE] = rand::thread_rng().gen();
+    let kdf = default_kdf(salt.to_vec());
+    let cipher = Cipher::Aes128Ctr(Aes128Ctr { iv: iv.to_vec().into() });
+    let (cipher_text, checksum) = eth2_keystore::encrypt(secret, password, &kdf, &cipher)
+        .map_err(|_| eyre::eyre!("Error encrypting key"))?;
+
+    Ok(Crypto {
+        kdf: KdfModule {
+            function: kdf.function(),
+            params: kdf,
+            message: eth2_keystore::json_keystore::EmptyString,
+        },
+        checksum: ChecksumModule {
+            function: Sha256Checksum::function(),
+            params: eth2_keystore::json_keystore::EmptyMap,
+            message: checksum.to_vec().into(),
+        },
+        cipher: CipherModule {
+            function: cipher.function(),
+            params: cipher,
+            message: cipher_text.into(),
+        },
+    })
+}
+
+/// Stores the password, delegation signature and keystore of a proxy. If any
+/// of them fails to be written, the files already written are removed so that
+/// no password is left without its keystore
 fn store_erc2335_key<T: PublicKey>(
     module_id: &ModuleId,
     delegation: SignedProxyDelegation<T>,
+    created_at: Option<u64>,
     secret: Vec<u8>,
     keys_path: &Path,
     secrets_path: &Path,
     scheme: EncryptionScheme,
+) -> eyre::Result<()> {
+    let mut written = Vec::new();
+
+    let Err(err) = write_erc2335_key(
+        module_id,
+        delegation,
+        created_at,
+        secret,
+        keys_path,
+        secrets_path,
+        scheme,
+        &mut written,
+    ) else {
+        return Ok(());
+    };
+
+    let orphaned: Vec<&PathBuf> =
+        written.iter().filter(|path| remove_file_if_exists(path).is_err()).collect();
+    if !orphaned.is_empty() {
+        error!(
+            proxy = %delegation.message.proxy,
+            ?orphaned,
+            "Failed to roll back a partially stored proxy, remove these files manually"
+        );
+    }
+
+    Err(err)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn write_erc2335_key<T: PublicKey>(
+    module_id: &ModuleId,
+    delegation: SignedProxyDelegation<T>,
+    created_at: Option<u64>,
+    secret: Vec<u8>,
+    keys_path: &Path,
+    secrets_path: &Path,
+    scheme: EncryptionScheme,
+    written: &mut Vec<PathBuf>,
 ) -> eyre::Result<()> {
     let proxy_pubkey = delegation.message.proxy;
 
@@ -410,6 +776,7 @@ fn store_erc2335_key<T: PublicKey>(
     std::fs::create_dir_all(&pass_path)?;
     let pass_path = pass_path.join(proxy_pubkey.to_string());
     let mut pass_file = std::fs::File::create(&pass_path)?;
+    written.push(pass_path);
     pass_file.write_all(password.as_bytes())?;
 
     let sig_path = keys_path
@@ -417,37 +784,30 @@ fn store_erc2335_key<T: PublicKey>(
         .join(&module_id.0)
         .join(scheme.to_string());
     std::fs::create_dir_all(&sig_path)?;
+    let version_path = sig_path.join(format!("{}.version", proxy_pubkey));
+    let created_path = sig_path.join(format!("{}.created", proxy_pubkey));
     let sig_path = sig_path.join(format!("{}.sig", proxy_pubkey));
 
-    let mut sig_file = std::fs::File::create(sig_path)?;
+    let mut sig_file = std::fs::File::create(&sig_path)?;
+    written.push(sig_path);
     sig_file.write_all(delegation.signature.to_string().as_bytes())?;
 
-    let salt: [u8; SALT_SIZE] = rand::thread_rng().gen();
-    let iv: [u8; IV_SIZE] = rand::thread_rng().gen();
-    let kdf = default_kdf(salt.to_vec());
-    let cipher = Cipher::Aes128Ctr(Aes128Ctr { iv: iv.to_vec().into() });
-    let (cipher_text, checksum) =
-        eth2_keystore::encrypt(&secret, password.as_bytes(), &kdf, &cipher)
-            .map_err(|_| eyre::eyre!("Error encrypting key"))?;
+    // V1 delegations have no version file, so that older signers can still
+    // load them
+    if !delegation.version.is_v1() {
+        let mut version_file = std::fs::File::create(&version_path)?;
+        written.push(version_path);
+        version_file.write_all(delegation.version.to_string().as_bytes())?;
+    }
+
+    if let Some(created_at) = created_at {
+        let mut created_file = std::fs::File::create(&created_path)?;
+        written.push(created_path);
+        created_file.write_all(created_at.to_string().as_bytes())?;
+    }
 
     let keystore = JsonKeystore {
-        crypto: Crypto {
-            kdf: KdfModule {
-                function: kdf.function(),
-                params: kdf,
-                message: eth2_keystore::json_keystore::EmptyString,
-            },
-            checksum: ChecksumModule {
-                function: Sha256Checksum::function(),
-                params: eth2_keystore::json_keystore::EmptyMap,
-                message: checksum.to_vec().into(),
-            },
-            cipher: CipherModule {
-                function: cipher.function(),
-                params: cipher,
-                message: cipher_text.into(),
-            },
-        },
+        crypto: encrypt_secret(&secret, password.as_bytes())?,
         uuid: Uuid::new_v4(),
         path: None,
         pubkey: format!("{:x}", delegation.message.proxy),
@@ -463,6 +823,7 @@ fn store_erc2335_key<T: PublicKey>(
     std::fs::create_dir_all(&json_path)?;
     let json_path = json_path.join(format!("{}.json", proxy_pubkey));
     let mut json_file = std::fs::File::create(&json_path)?;
+    written.push(json_path);
     json_file.write_all(serde_json::to_string(&keystore)?.as_bytes())?;
 
     Ok(())
@@ -471,7 +832,6 @@ fn store_erc2335_key<T: PublicKey>(
 #[cfg(test)]
 mod test {
     use hex::FromHex;
-    use tree_hash::TreeHash;
 
     use super::*;
     use crate::{
@@ -480,6 +840,48 @@ mod test {
         types::Chain,
     };
 
+    async fn test_bls_proxy(
+        version: DelegationVersion,
+        created_at: Option<u64>,
+    ) -> (ConsensusSigner, BlsProxySigner) {
+        let consensus_signer = ConsensusSigner::new_random();
+        let signer = BlsSigner::new_random();
+        let message =
+            ProxyDelegationBls { delegator: consensus_signer.pubkey(), proxy: signer.pubkey() };
+        let signature = consensus_signer.sign(Chain::Mainnet, message.object_root(version)).await;
+        let delegation = SignedProxyDelegationBls { signature, message, version };
+
+        (consensus_signer, BlsProxySigner { signer, delegation, created_at })
+    }
+
+    #[tokio::test]
+    async fn test_file_store_encryption() {
+        let tmp_path = std::env::temp_dir().join("test_file_store_encryption");
+        let _ = std::fs::remove_dir_all(&tmp_path);
+        let path = tmp_path.join("TEST_MODULE").join("bls").join("proxy");
+
+        let (_, proxy_signer) = test_bls_proxy(DelegationVersion::V1, None).await;
+        let secret = Bytes::from(proxy_signer.signer.secret());
+
+        // a plaintext proxy is encrypted in place when loaded with a password
+        write_file_proxy(&path, secret.clone(), proxy_signer.delegation.clone(), None, None)
+            .unwrap();
+        let (loaded, _, _) = read_file_proxy::<BlsPublicKey>(&path, Some("password")).unwrap();
+        assert_eq!(loaded, secret);
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.contains("crypto") && !content.contains(&secret.to_string()));
+
+        let (loaded, loaded_delegation, _) =
+            read_file_proxy::<BlsPublicKey>(&path, Some("password")).unwrap();
+        assert_eq!(loaded, secret);
+        assert_eq!(loaded_delegation.message.proxy, proxy_signer.pubkey());
+
+        let err = read_file_proxy::<BlsPublicKey>(&path, Some("wrong")).unwrap_err();
+        assert!(err.to_string().contains("wrong proxy store password"));
+        assert!(read_file_proxy::<BlsPublicKey>(&path, None).is_err());
+    }
+
     #[tokio::test]
     async fn test_erc2335_storage_format() {
         let tmp_path = std::env::temp_dir().join("test_erc2335_storage_format");
@@ -491,22 +893,7 @@ mod test {
         };
 
         let module_id = ModuleId("TEST_MODULE".to_string());
-        let consensus_signer = ConsensusSigner::new_from_bytes(&hex!(
-            "0088e364a5396a81b50febbdc8784663fb9089b5e67cbdc173991a00c587673f"
-        ))
-        .unwrap();
-        let proxy_signer = BlsSigner::new_from_bytes(&hex!(
-            "13000f8b3d7747e7754022720d33d5b506490429f3d593162f00e254f97d2940"
-        ))
-        .unwrap();
-
-        let message = ProxyDelegationBls {
-            delegator: consensus_signer.pubkey(),
-            proxy: proxy_signer.pubkey(),
-        };
-        let signature = consensus_signer.sign(Chain::Mainnet, message.tree_hash_root().0).await;
-        let delegation = SignedProxyDelegationBls { signature, message };
-        let proxy_signer = BlsProxySigner { signer: proxy_signer, delegation };
+        let (consensus_signer, proxy_signer) = test_bls_proxy(DelegationVersion::V1, None).await;
 
         store.store_proxy_bls(&module_id, &proxy_signer).unwrap();
 
@@ -537,7 +924,7 @@ mod test {
 
         let sig = FixedBytes::from_hex(std::fs::read_to_string(sig_path).unwrap());
         assert!(sig.is_ok());
-        assert_eq!(sig.unwrap(), signature);
+        assert_eq!(sig.unwrap(), proxy_signer.delegation.signature);
     }
 
     #[test]
@@ -604,22 +991,7 @@ mod test {
         };
 
         let module_id = ModuleId("TEST_MODULE".to_string());
-        let consensus_signer = ConsensusSigner::new_from_bytes(&hex!(
-            "0088e364a5396a81b50febbdc8784663fb9089b5e67cbdc173991a00c587673f"
-        ))
-        .unwrap();
-        let proxy_signer = BlsSigner::new_from_bytes(&hex!(
-            "13000f8b3d7747e7754022720d33d5b506490429f3d593162f00e254f97d2940"
-        ))
-        .unwrap();
-
-        let message = ProxyDelegationBls {
-            delegator: consensus_signer.pubkey(),
-            proxy: proxy_signer.pubkey(),
-        };
-        let signature = consensus_signer.sign(Chain::Mainnet, message.tree_hash_root().0).await;
-        let delegation = SignedProxyDelegationBls { signature, message };
-        let proxy_signer = BlsProxySigner { signer: proxy_signer, delegation };
+        let (consensus_signer, proxy_signer) = test_bls_proxy(DelegationVersion::V1, None).await;
 
         store.store_proxy_bls(&module_id, &proxy_signer).unwrap();
 
@@ -659,4 +1031,157 @@ mod test {
             .get(&ModuleId("TEST_MODULE".into()))
             .is_some_and(|keys| keys.contains(&proxy_signer.pubkey())));
     }
+
+    #[tokio::test]
+    async fn test_erc2335_delegation_version() {
+        let tmp_path = std::env::temp_dir().join("test_erc2335_delegation_version");
+        let _ = std::fs::remove_dir_all(&tmp_path);
+        let keys_path = tmp_path.join("keys");
+        let store = ProxyStore::ERC2335 {
+            keys_path: keys_path.clone(),
+            secrets_path: tmp_path.join("secrets"),
+        };
+
+        let module_id = ModuleId("TEST_MODULE".to_string());
+        let (consensus_signer, proxy_signer) = test_bls_proxy(DelegationVersion::V2, None).await;
+
+        store.store_proxy_bls(&module_id, &proxy_signer).unwrap();
+
+        let (proxy_signers, _, _) = store.load_proxies().unwrap();
+        let loaded = &proxy_signers.bls_signers[&proxy_signer.pubkey()];
+        assert_eq!(loaded.delegation.version, DelegationVersion::V2);
+        assert!(loaded.delegation.validate(Chain::Mainnet).is_ok());
+
+        let version_path = keys_path
+            .join(consensus_signer.pubkey().to_string())
+            .join("TEST_MODULE")
+            .join("bls")
+            .join(format!("{}.version", proxy_signer.pubkey()));
+        store
+            .delete_proxy(&module_id, &proxy_signer.delegation.message, EncryptionScheme::Bls)
+            .unwrap();
+        assert!(!version_path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_proxy_creation_time() {
+        let tmp_path = std::env::temp_dir().join("test_proxy_creation_time");
+        let _ = std::fs::remove_dir_all(&tmp_path);
+        let stores = [
+            ProxyStore::File { proxy_dir: tmp_path.join("file"), encrypted: false },
+            ProxyStore::ERC2335 {
+                keys_path: tmp_path.join("keys"),
+                secrets_path: tmp_path.join("secrets"),
+            },
+        ];
+
+        let module_id = ModuleId("TEST_MODULE".to_string());
+
+        for store in stores {
+            let mut proxies = Vec::new();
+            for created_at in [Some(1_700_000_000), None] {
+                let (_, proxy_signer) = test_bls_proxy(DelegationVersion::V1, created_at).await;
+                store.store_proxy_bls(&module_id, &proxy_signer).unwrap();
+                proxies.push(proxy_signer);
+            }
+
+            let (proxy_signers, _, _) = store.load_proxies().unwrap();
+            for proxy in proxies {
+                assert_eq!(proxy_signers.bls_signers[&proxy.pubkey()].created_at, proxy.created_at);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_erc2335_delete() {
+        let tmp_path = std::env::temp_dir().join("test_erc2335_delete");
+        let _ = std::fs::remove_dir_all(&tmp_path);
+        let keys_path = tmp_path.join("keys");
+        let secrets_path = tmp_path.join("secrets");
+        let store = ProxyStore::ERC2335 {
+            keys_path: keys_path.clone(),
+            secrets_path: secrets_path.clone(),
+        };
+
+        let module_id = ModuleId("TEST_MODULE".to_string());
+        let (consensus_signer, proxy_signer) = test_bls_proxy(DelegationVersion::V1, None).await;
+
+        let message = proxy_signer.delegation.message;
+
+        store.store_proxy_bls(&module_id, &proxy_signer).unwrap();
+        store.delete_proxy(&module_id, &message, EncryptionScheme::Bls).unwrap();
+
+        let (proxy_signers, _, _) = store.load_proxies().unwrap();
+        assert!(proxy_signers.bls_signers.is_empty());
+
+        let pass_path = secrets_path
+            .join(consensus_signer.pubkey().to_string())
+            .join("TEST_MODULE")
+            .join("bls")
+            .join(proxy_signer.pubkey().to_string());
+        assert!(!pass_path.exists());
+
+        // deleting again is a no-op
+        store.delete_proxy(&module_id, &message, EncryptionScheme::Bls).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_erc2335_prune_orphan_passwords() {
+        let tmp_path = std::env::temp_dir().join("test_erc2335_prune_orphan_passwords");
+        let _ = std::fs::remove_dir_all(&tmp_path);
+        let keys_path = tmp_path.join("keys");
+        let secrets_path = tmp_path.join("secrets");
+        let store = ProxyStore::ERC2335 {
+            keys_path: keys_path.clone(),
+            secrets_path: secrets_path.clone(),
+        };
+
+        let module_id = ModuleId("TEST_MODULE".to_string());
+        let (consensus_signer, proxy_signer) = test_bls_proxy(DelegationVersion::V1, None).await;
+        store.store_proxy_bls(&module_id, &proxy_signer).unwrap();
+
+        let orphan_path = secrets_path
+            .join(consensus_signer.pubkey().to_string())
+            .join("TEST_MODULE")
+            .join("bls")
+            .join(BlsSigner::new_random().pubkey().to_string());
+        std::fs::write(&orphan_path, "password").unwrap();
+
+        // too recent, its keystore may still be being written
+        let pruned = store.prune_orphan_passwords(Duration::from_secs(3600)).unwrap();
+        assert!(pruned.is_empty());
+
+        let pruned = store.prune_orphan_passwords(Duration::ZERO).unwrap();
+        assert_eq!(pruned, vec![orphan_path.clone()]);
+        assert!(!orphan_path.exists());
+
+        let (proxy_signers, _, _) = store.load_proxies().unwrap();
+        assert!(proxy_signers.bls_signers.contains_key(&proxy_signer.pubkey()));
+    }
+
+    #[tokio::test]
+    async fn test_erc2335_rollback_partial_store() {
+        let tmp_path = std::env::temp_dir().join("test_erc2335_rollback_partial_store");
+        let _ = std::fs::remove_dir_all(&tmp_path);
+        std::fs::create_dir_all(&tmp_path).unwrap();
+
+        // a file in place of the keys directory makes the signature write fail
+        // after the password was stored
+        let keys_path = tmp_path.join("keys");
+        std::fs::write(&keys_path, "").unwrap();
+        let secrets_path = tmp_path.join("secrets");
+        let store = ProxyStore::ERC2335 { keys_path, secrets_path: secrets_path.clone() };
+
+        let module_id = ModuleId("TEST_MODULE".to_string());
+        let (consensus_signer, proxy_signer) = test_bls_proxy(DelegationVersion::V1, None).await;
+
+        assert!(store.store_proxy_bls(&module_id, &proxy_signer).is_err());
+
+        let pass_path = secrets_path
+            .join(consensus_signer.pubkey().to_string())
+            .join("TEST_MODULE")
+            .join("bls")
+            .join(proxy_signer.pubkey().to_string());
+        assert!(!pass_path.exists());
+    }
 }
@@ -25,6 +25,9 @@ pub struct BlsProxySigner {
     #[deref]
     pub signer: BlsSigner,
     pub delegation: SignedProxyDelegationBls,
+    /// Unix timestamp in seconds of when the proxy was generated, unknown for
+    /// proxies stored before it was recorded
+    pub created_at: Option<u64>,
 }
 
 #[derive(Clone, Deref)]
@@ -32,6 +35,9 @@ pub struct EcdsaProxySigner {
     #[deref]
     pub signer: EcdsaSigner,
     pub delegation: SignedProxyDelegationEcdsa,
+    /// Unix timestamp in seconds of when the proxy was generated, unknown for
+    /// proxies stored before it was recorded
+    pub created_at: Option<u64>,
 }
 
 #[derive(Default)]
@@ -13,12 +13,17 @@ use rand::{distributions::Alphanumeric, Rng};
 use reqwest::header::HeaderMap;
 use serde::{de::DeserializeOwned, Serialize};
 use serde_json::Value;
-use tracing::Level;
+use tracing::{Level, Subscriber};
 use tracing_appender::{non_blocking::WorkerGuard, rolling::Rotation};
-use tracing_subscriber::{fmt::Layer, prelude::*, EnvFilter};
+use tracing_subscriber::{
+    fmt::{Layer, MakeWriter},
+    prelude::*,
+    registry::LookupSpan,
+    EnvFilter,
+};
 
 use crate::{
-    config::{load_optional_env_var, LogsSettings, PBS_MODULE_NAME},
+    config::{load_optional_env_var, LogFormat, LogsSettings, LOG_FORMAT_ENV, PBS_MODULE_NAME},
     pbs::HEADER_VERSION_VALUE,
     types::Chain,
 };
@@ -174,6 +179,11 @@ pub fn initialize_tracing_log(module_id: &str) -> eyre::Result<WorkerGuard> {
 
     let stdout_filter = format_crates_filter(Level::INFO.as_str(), stdout_log_level.as_str());
 
+    let stdout_format = match load_optional_env_var(LOG_FORMAT_ENV) {
+        Some(format) => format.parse()?,
+        None => settings.stdout_format,
+    };
+
     if use_file_logs {
         // Log all events to a rolling log file.
         let mut builder =
@@ -192,8 +202,7 @@ pub fn initialize_tracing_log(module_id: &str) -> eyre::Result<WorkerGuard> {
         let file_log_level = stdout_log_level.max(Level::DEBUG);
         let file_log_filter = format_crates_filter(Level::INFO.as_str(), file_log_level.as_str());
 
-        let stdout_layer =
-            tracing_subscriber::fmt::layer().with_target(false).with_filter(stdout_filter);
+        let stdout_layer = stdout_layer(stdout_format, std::io::stdout).with_filter(stdout_filter);
 
         let file_layer = Layer::new()
             .json()
@@ -206,15 +215,31 @@ pub fn initialize_tracing_log(module_id: &str) -> eyre::Result<WorkerGuard> {
         Ok(guard)
     } else {
         let (writer, guard) = tracing_appender::non_blocking(std::io::stdout());
-        let stdout_layer = tracing_subscriber::fmt::layer()
-            .with_target(false)
-            .with_writer(writer)
-            .with_filter(stdout_filter);
+        let stdout_layer = stdout_layer(stdout_format, writer).with_filter(stdout_filter);
         tracing_subscriber::registry().with(stdout_layer).init();
         Ok(guard)
     }
 }
 
+/// Layer writing the logs in the given format, to stdout outside of tests
+pub fn stdout_layer<S, W>(
+    format: LogFormat,
+    writer: W,
+) -> Box<dyn tracing_subscriber::Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let layer = tracing_subscriber::fmt::layer().with_target(false).with_writer(writer);
+
+    match format {
+        LogFormat::Text => layer.boxed(),
+        LogFormat::Pretty => layer.pretty().boxed(),
+        // same fields as the file logs
+        LogFormat::Json => layer.json().with_current_span(false).with_span_list(true).boxed(),
+    }
+}
+
 pub fn initialize_pbs_tracing_log() -> eyre::Result<WorkerGuard> {
     initialize_tracing_log(PBS_MODULE_NAME)
 }
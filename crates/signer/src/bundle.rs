@@ -0,0 +1,180 @@
+use alloy::primitives::{Bytes, B256};
+use cb_common::{
+    commit::request::{PublicKey, SignedProxyDelegation},
+    constants::GENESIS_VALIDATORS_ROOT,
+    signer::{
+        encrypt_secret, BlsProxySigner, BlsPublicKey, BlsSigner, EcdsaProxySigner, EcdsaPublicKey,
+        EcdsaSigner,
+    },
+    types::{Chain, ModuleId},
+};
+use eth2_keystore::json_keystore::Crypto;
+use eyre::{ensure, eyre, Context};
+use serde::{Deserialize, Serialize};
+
+/// Version of the bundle format, bumped on breaking changes
+pub const PROXY_BUNDLE_VERSION: u64 = 1;
+
+/// Proxy keys and delegations of every module, used to move proxies between
+/// signers. The proxies are encrypted with a passphrase chosen on export
+#[derive(Serialize, Deserialize)]
+pub struct ProxyBundle {
+    pub version: u64,
+    /// Chain the delegations were signed for
+    pub chain: Chain,
+    /// Genesis validators root the delegations were signed with
+    pub genesis_validators_root: B256,
+    /// The serialized `ProxyBundleContents`, encrypted like an ERC2335
+    /// keystore. Its checksum detects a wrong passphrase and any change to the
+    /// proxies
+    pub crypto: Crypto,
+}
+
+/// Decrypted proxies of a bundle. The chain is repeated so that the
+/// unencrypted one can't be changed without it being noticed
+#[derive(Serialize, Deserialize)]
+pub struct ProxyBundleContents {
+    pub chain: Chain,
+    pub genesis_validators_root: B256,
+    pub proxies_bls: Vec<BundledProxy<BlsPublicKey>>,
+    pub proxies_ecdsa: Vec<BundledProxy<EcdsaPublicKey>>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BundledProxy<T: PublicKey> {
+    pub module_id: ModuleId,
+    pub secret: Bytes,
+    pub delegation: SignedProxyDelegation<T>,
+    /// Unix timestamp in seconds of when the proxy was generated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<u64>,
+}
+
+/// Result of an import, proxies already loaded for the same module are skipped
+#[derive(Debug, Default, Serialize)]
+pub struct ImportedProxies {
+    pub imported: usize,
+    pub skipped: usize,
+}
+
+impl ProxyBundleContents {
+    /// Encrypts the proxies with the passphrase
+    pub fn seal(self, passphrase: &[u8]) -> eyre::Result<ProxyBundle> {
+        ensure!(!passphrase.is_empty(), "the passphrase is empty");
+
+        let crypto = encrypt_secret(&serde_json::to_vec(&self)?, passphrase)?;
+
+        Ok(ProxyBundle {
+            version: PROXY_BUNDLE_VERSION,
+            chain: self.chain,
+            genesis_validators_root: self.genesis_validators_root,
+            crypto,
+        })
+    }
+}
+
+impl ProxyBundle {
+    /// Checks the bundle was exported for the same chain
+    pub fn check_compatible(&self, chain: Chain) -> eyre::Result<()> {
+        let genesis_validators_root = B256::from(GENESIS_VALIDATORS_ROOT);
+
+        ensure!(
+            self.version == PROXY_BUNDLE_VERSION,
+            "unsupported bundle version {}, expected {PROXY_BUNDLE_VERSION}",
+            self.version
+        );
+        ensure!(
+            self.chain.genesis_fork_version() == chain.genesis_fork_version(),
+            "bundle was exported for {}, but the signer is running on {chain}",
+            self.chain
+        );
+        ensure!(
+            self.genesis_validators_root == genesis_validators_root,
+            "bundle was exported with genesis validators root {}, but the signer uses {}",
+            self.genesis_validators_root,
+            genesis_validators_root
+        );
+
+        Ok(())
+    }
+
+    /// Decrypts the proxies, checking that the bundle is compatible and wasn't
+    /// changed since it was exported
+    pub fn open(self, chain: Chain, passphrase: &[u8]) -> eyre::Result<ProxyBundleContents> {
+        self.check_compatible(chain)?;
+
+        let plaintext = eth2_keystore::decrypt(passphrase, &self.crypto)
+            .map_err(|_| eyre!("wrong passphrase or corrupted bundle"))?;
+        let contents: ProxyBundleContents =
+            serde_json::from_slice(plaintext.as_bytes()).wrap_err("invalid bundle contents")?;
+        ensure!(
+            contents.chain == self.chain &&
+                contents.genesis_validators_root == self.genesis_validators_root,
+            "the chain of the bundle doesn't match its contents"
+        );
+
+        Ok(contents)
+    }
+}
+
+impl BundledProxy<BlsPublicKey> {
+    pub fn from_signer(module_id: ModuleId, proxy: &BlsProxySigner) -> Self {
+        Self {
+            module_id,
+            secret: Bytes::from(proxy.signer.secret()),
+            delegation: proxy.delegation,
+            created_at: proxy.created_at,
+        }
+    }
+
+    /// Rebuilds the proxy signer, checking that the secret matches the
+    /// delegated key and that the delegation is signed by the consensus key
+    pub fn into_signer(self, chain: Chain) -> eyre::Result<(ModuleId, BlsProxySigner)> {
+        let signer = BlsSigner::new_from_bytes(&self.secret)?;
+        ensure!(
+            signer.pubkey() == self.delegation.message.proxy,
+            "secret doesn't match proxy {}",
+            self.delegation.message.proxy
+        );
+        self.delegation.validate(chain).map_err(|err| {
+            eyre!("invalid delegation for {}: {err}", self.delegation.message.proxy)
+        })?;
+
+        Ok((self.module_id, BlsProxySigner {
+            signer,
+            delegation: self.delegation,
+            created_at: self.created_at,
+        }))
+    }
+}
+
+impl BundledProxy<EcdsaPublicKey> {
+    pub fn from_signer(module_id: ModuleId, proxy: &EcdsaProxySigner) -> Self {
+        Self {
+            module_id,
+            secret: Bytes::from(proxy.signer.secret()),
+            delegation: proxy.delegation,
+            created_at: proxy.created_at,
+        }
+    }
+
+    /// Rebuilds the proxy signer, checking that the secret matches the
+    /// delegated key and that the delegation is signed by the consensus key
+    pub fn into_signer(self, chain: Chain) -> eyre::Result<(ModuleId, EcdsaProxySigner)> {
+        let signer = EcdsaSigner::new_from_bytes(&self.secret)?;
+        ensure!(
+            signer.pubkey() == self.delegation.message.proxy,
+            "secret doesn't match proxy {}",
+            self.delegation.message.proxy
+        );
+        self.delegation.validate(chain).map_err(|err| {
+            eyre!("invalid delegation for {}: {err}", self.delegation.message.proxy)
+        })?;
+
+        Ok((self.module_id, EcdsaProxySigner {
+            signer,
+            delegation: self.delegation,
+            created_at: self.created_at,
+        }))
+    }
+}
@@ -1,3 +1,39 @@
 pub const GET_PUBKEYS_ENDPOINT_TAG: &str = "get_pubkeys";
 pub const GENERATE_PROXY_KEY_ENDPOINT_TAG: &str = "generate_proxy_key";
+pub const DELETE_PROXY_KEYS_ENDPOINT_TAG: &str = "delete_proxy_keys";
+pub const CHECK_KEYS_ENDPOINT_TAG: &str = "check_keys";
+pub const PROOF_OF_POSSESSION_ENDPOINT_TAG: &str = "proof_of_possession";
+pub const REQUEST_EXAMPLES_ENDPOINT_TAG: &str = "request_examples";
+pub const PERMISSIONS_ENDPOINT_TAG: &str = "permissions";
 pub const REQUEST_SIGNATURE_ENDPOINT_TAG: &str = "request_signature";
+pub const SIGN_VALIDATOR_REGISTRATION_ENDPOINT_TAG: &str = "sign_validator_registration";
+pub const SIGNER_CONFIG_ENDPOINT_TAG: &str = "signer_config";
+pub const SIGNER_RELOAD_ENDPOINT_TAG: &str = "signer_reload";
+pub const SIGNER_ALL_PUBKEYS_ENDPOINT_TAG: &str = "signer_all_pubkeys";
+pub const SIGNER_EXPORT_PROXIES_ENDPOINT_TAG: &str = "signer_export_proxies";
+pub const SIGNER_IMPORT_PROXIES_ENDPOINT_TAG: &str = "signer_import_proxies";
+pub const SIGNER_REDELEGATE_PROXIES_ENDPOINT_TAG: &str = "signer_redelegate_proxies";
+pub const SIGNER_MODULE_DENYLIST_ENDPOINT_TAG: &str = "signer_module_denylist";
+pub const SIGNER_KEY_USAGE_ENDPOINT_TAG: &str = "signer_key_usage";
+pub const SIGNER_REENCRYPT_KEYSTORES_ENDPOINT_TAG: &str = "signer_reencrypt_keystores";
+pub const SIGNER_STATS_ENDPOINT_TAG: &str = "signer_stats";
+
+/// Longer request ids sent by modules are replaced with a generated one
+pub const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// How often to check the JWTs file for changes
+pub const JWTS_RELOAD_INTERVAL_SECS: u64 = 10;
+
+/// Min age of a proxy password before it can be pruned, so that a proxy being
+/// generated is never affected
+pub const ORPHAN_PASSWORD_GRACE_SECS: u64 = 600;
+
+/// Max validators fetched per request to the beacon node, to keep the url short
+pub const BEACON_VALIDATORS_BATCH_SIZE: usize = 100;
+
+/// Max time of a request to the beacon node, so an unresponsive one can't
+/// hang the startup or a reload
+pub const BEACON_REQUEST_TIMEOUT_SECS: u64 = 30;
+
+/// How often the ended quota windows are reset
+pub const QUOTA_RESET_INTERVAL_SECS: u64 = 1;
@@ -0,0 +1,60 @@
+use std::collections::{HashMap, HashSet};
+
+use cb_common::types::ModuleId;
+
+/// Modules refused access. The runtime toggles take precedence over the
+/// config, and are kept when the config is reloaded so a module denied during
+/// an incident isn't re-enabled by a reload. They're reset on restart
+#[derive(Default)]
+pub struct ModuleDenylist {
+    configured: HashSet<ModuleId>,
+    toggles: HashMap<ModuleId, bool>,
+}
+
+impl ModuleDenylist {
+    pub fn new(configured: impl IntoIterator<Item = ModuleId>) -> Self {
+        Self { configured: configured.into_iter().collect(), toggles: HashMap::new() }
+    }
+
+    /// Replaces the modules denied in the config, keeping the runtime toggles
+    pub fn set_configured(&mut self, configured: impl IntoIterator<Item = ModuleId>) {
+        self.configured = configured.into_iter().collect();
+    }
+
+    pub fn toggle(&mut self, module_id: ModuleId, denied: bool) {
+        self.toggles.insert(module_id, denied);
+    }
+
+    pub fn is_denied(&self, module_id: &ModuleId) -> bool {
+        self.toggles.get(module_id).copied().unwrap_or_else(|| self.configured.contains(module_id))
+    }
+
+    pub fn denied_modules(&self) -> Vec<ModuleId> {
+        let toggled = self.toggles.iter().filter(|(_, denied)| **denied).map(|(id, _)| id);
+        let configured = self.configured.iter().filter(|id| !self.toggles.contains_key(*id));
+        toggled.chain(configured).cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toggles_survive_reload() {
+        let module_a = ModuleId("MODULE_A".to_string());
+        let module_b = ModuleId("MODULE_B".to_string());
+        let mut denylist = ModuleDenylist::new([module_a.clone()]);
+
+        denylist.toggle(module_a.clone(), false);
+        denylist.toggle(module_b.clone(), true);
+        assert!(!denylist.is_denied(&module_a));
+        assert!(denylist.is_denied(&module_b));
+
+        // a reload with a config that doesn't deny module_b keeps it denied
+        denylist.set_configured([module_a.clone()]);
+        assert!(!denylist.is_denied(&module_a));
+        assert!(denylist.is_denied(&module_b));
+        assert_eq!(denylist.denied_modules(), vec![module_b]);
+    }
+}
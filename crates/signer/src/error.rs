@@ -2,7 +2,9 @@ use alloy::hex;
 use axum::{
     http::StatusCode,
     response::{IntoResponse, Response},
+    Json,
 };
+use serde::Serialize;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -16,6 +18,24 @@ pub enum SignerModuleError {
     #[error("unknown proxy signer: 0x{}", hex::encode(.0))]
     UnknownProxySigner(Vec<u8>),
 
+    #[error("invalid request: {0}")]
+    InvalidRequest(String),
+
+    #[error("signature quota exceeded")]
+    QuotaExceeded,
+
+    #[error("invalid nonce: {0}")]
+    InvalidNonce(String),
+
+    #[error("no proxy store configured, the proxy would be lost on restart. Request an ephemeral proxy to generate it anyway")]
+    MissingProxyStore,
+
+    #[error("unknown route: {0}")]
+    UnknownRoute(String),
+
+    #[error("signing timed out")]
+    Timeout,
+
     #[error("internal error {0}")]
     Internal(String),
 }
@@ -26,9 +46,24 @@ impl IntoResponse for SignerModuleError {
             SignerModuleError::Unauthorized => StatusCode::UNAUTHORIZED,
             SignerModuleError::UnknownConsensusSigner(_) => StatusCode::NOT_FOUND,
             SignerModuleError::UnknownProxySigner(_) => StatusCode::NOT_FOUND,
+            SignerModuleError::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+            SignerModuleError::QuotaExceeded => StatusCode::TOO_MANY_REQUESTS,
+            SignerModuleError::InvalidNonce(_) => StatusCode::CONFLICT,
+            SignerModuleError::MissingProxyStore => StatusCode::CONFLICT,
+            SignerModuleError::UnknownRoute(_) => StatusCode::NOT_FOUND,
+            SignerModuleError::Timeout => StatusCode::GATEWAY_TIMEOUT,
             SignerModuleError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
         };
 
-        (status, self.to_string()).into_response()
+        let body = ErrorBody { code: status.as_u16(), message: self.to_string() };
+
+        (status, Json(body)).into_response()
     }
 }
+
+/// Error body documented in the Signer API
+#[derive(Serialize)]
+struct ErrorBody {
+    code: u16,
+    message: String,
+}
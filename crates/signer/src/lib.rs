@@ -1,5 +1,12 @@
+pub mod bundle;
 mod constants;
+mod denylist;
 pub mod error;
 pub mod manager;
 mod metrics;
+mod nonce;
+mod quota;
 pub mod service;
+mod stats;
+mod usage;
+mod withdrawal;
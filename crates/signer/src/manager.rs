@@ -1,21 +1,38 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use alloy::rpc::types::beacon::BlsSignature;
+use alloy::{primitives::B256, rpc::types::beacon::BlsSignature};
 use cb_common::{
     commit::request::{
-        ConsensusProxyMap, ProxyDelegationBls, ProxyDelegationEcdsa, SignedProxyDelegationBls,
-        SignedProxyDelegationEcdsa,
+        ConsensusProxyMap, DelegationVersion, EncryptionScheme, KeyKind, KeyStatus, OrphanProxies,
+        ProxyDelegationBls, ProxyDelegationEcdsa, ProxyPublicKey, SignedProxyDelegationBls,
+        SignedProxyDelegationEcdsa, ValidatorRegistrationMessage,
     },
+    constants::{COMMIT_BOOST_DOMAIN, GENESIS_VALIDATORS_ROOT},
+    signature::{compute_domain, compute_signing_root},
     signer::{
-        BlsProxySigner, BlsPublicKey, BlsSigner, ConsensusSigner, EcdsaProxySigner, EcdsaPublicKey,
-        EcdsaSignature, EcdsaSigner, ProxySigners, ProxyStore,
+        verify_bls_signature, verify_proof_of_possession, BlsProxySigner, BlsPublicKey, BlsSigner,
+        ConsensusSigner, EcdsaProxySigner, EcdsaPublicKey, EcdsaSignature, EcdsaSigner,
+        ProxySigners, ProxyStore,
     },
     types::{Chain, ModuleId},
+    utils::utcnow_sec,
 };
-use eyre::OptionExt;
+use eyre::{bail, ensure};
+use serde::Serialize;
+use tracing::{error, warn};
 use tree_hash::TreeHash;
 
-use crate::error::SignerModuleError;
+use crate::{
+    bundle::{BundledProxy, ImportedProxies, ProxyBundleContents},
+    error::SignerModuleError,
+};
+
+/// New delegations of the proxies moved to another consensus key
+#[derive(Debug, Default, Serialize)]
+pub struct RedelegatedProxies {
+    pub proxies_bls: Vec<SignedProxyDelegationBls>,
+    pub proxies_ecdsa: Vec<SignedProxyDelegationEcdsa>,
+}
 
 pub struct SigningManager {
     chain: Chain,
@@ -27,6 +44,11 @@ pub struct SigningManager {
     /// manager.
     proxy_pubkeys_bls: HashMap<ModuleId, Vec<BlsPublicKey>>,
     proxy_pubkeys_ecdsa: HashMap<ModuleId, Vec<EcdsaPublicKey>>,
+    /// If set, only these consensus keys are allowed to sign, regardless of
+    /// which keys were loaded
+    consensus_allowlist: Option<HashSet<BlsPublicKey>>,
+    /// Whether to verify BLS signatures before returning them
+    verify_signatures: bool,
 }
 
 impl SigningManager {
@@ -38,6 +60,8 @@ impl SigningManager {
             proxy_signers: Default::default(),
             proxy_pubkeys_bls: Default::default(),
             proxy_pubkeys_ecdsa: Default::default(),
+            consensus_allowlist: None,
+            verify_signatures: false,
         };
 
         if let Some(store) = &manager.proxy_store {
@@ -50,20 +74,48 @@ impl SigningManager {
         Ok(manager)
     }
 
+    /// Restricts consensus signing to the given keys
+    pub fn set_consensus_allowlist(&mut self, allowlist: impl IntoIterator<Item = BlsPublicKey>) {
+        self.consensus_allowlist = Some(allowlist.into_iter().collect());
+    }
+
+    /// Enables verification of every BLS signature against the signer pubkey
+    /// before it's returned
+    pub fn set_verify_signatures(&mut self, verify_signatures: bool) {
+        self.verify_signatures = verify_signatures;
+    }
+
+    /// Removes the allowlist, all loaded consensus keys can sign
+    pub fn clear_consensus_allowlist(&mut self) {
+        self.consensus_allowlist = None;
+    }
+
     pub fn add_consensus_signer(&mut self, signer: ConsensusSigner) {
         self.consensus_signers.insert(signer.pubkey(), signer);
     }
 
+    /// Replaces all the consensus signers, proxies are kept
+    pub fn replace_consensus_signers(&mut self, signers: Vec<ConsensusSigner>) {
+        self.consensus_signers =
+            signers.into_iter().map(|signer| (signer.pubkey(), signer)).collect();
+    }
+
     pub fn add_proxy_signer_bls(
         &mut self,
         proxy: BlsProxySigner,
         module_id: ModuleId,
     ) -> eyre::Result<()> {
+        let proxy_pubkey = proxy.pubkey();
+        ensure!(
+            !self.consensus_signers.contains_key(&proxy_pubkey) &&
+                !self.proxy_signers.bls_signers.contains_key(&proxy_pubkey),
+            "proxy pubkey {proxy_pubkey} is already in use"
+        );
+
         if let Some(store) = &self.proxy_store {
             store.store_proxy_bls(&module_id, &proxy)?;
         }
 
-        let proxy_pubkey = proxy.pubkey();
         self.proxy_signers.bls_signers.insert(proxy.pubkey(), proxy);
         self.proxy_pubkeys_bls.entry(module_id).or_default().push(proxy_pubkey);
 
@@ -75,29 +127,93 @@ impl SigningManager {
         proxy: EcdsaProxySigner,
         module_id: ModuleId,
     ) -> eyre::Result<()> {
+        let proxy_pubkey = proxy.pubkey();
+        ensure!(
+            !self.proxy_signers.ecdsa_signers.contains_key(&proxy_pubkey),
+            "proxy pubkey {proxy_pubkey} is already in use"
+        );
+
         if let Some(store) = &self.proxy_store {
             store.store_proxy_ecdsa(&module_id, &proxy)?;
         }
 
-        let proxy_pubkey = proxy.pubkey();
         self.proxy_signers.ecdsa_signers.insert(proxy.pubkey(), proxy);
         self.proxy_pubkeys_ecdsa.entry(module_id).or_default().push(proxy_pubkey);
 
         Ok(())
     }
 
+    /// Deletes a proxy of the module, both from memory and from the store
+    pub fn delete_proxy_bls(
+        &mut self,
+        module_id: &ModuleId,
+        pubkey: &BlsPublicKey,
+    ) -> Result<(), SignerModuleError> {
+        if !self.has_proxy_bls_for_module(pubkey, module_id) {
+            return Err(SignerModuleError::UnknownProxySigner(pubkey.to_vec()));
+        }
+
+        let delegation = self.get_delegation_bls(pubkey)?;
+        if let Some(store) = &self.proxy_store {
+            store
+                .delete_proxy(module_id, &delegation.message, EncryptionScheme::Bls)
+                .map_err(|err| SignerModuleError::Internal(err.to_string()))?;
+        }
+
+        self.proxy_signers.bls_signers.remove(pubkey);
+        if let Some(keys) = self.proxy_pubkeys_bls.get_mut(module_id) {
+            keys.retain(|key| key != pubkey);
+        }
+
+        Ok(())
+    }
+
+    /// Deletes a proxy of the module, both from memory and from the store
+    pub fn delete_proxy_ecdsa(
+        &mut self,
+        module_id: &ModuleId,
+        pubkey: &EcdsaPublicKey,
+    ) -> Result<(), SignerModuleError> {
+        if !self.has_proxy_ecdsa_for_module(pubkey, module_id) {
+            return Err(SignerModuleError::UnknownProxySigner(pubkey.to_vec()));
+        }
+
+        let delegation = self.get_delegation_ecdsa(pubkey)?;
+        if let Some(store) = &self.proxy_store {
+            store
+                .delete_proxy(module_id, &delegation.message, EncryptionScheme::Ecdsa)
+                .map_err(|err| SignerModuleError::Internal(err.to_string()))?;
+        }
+
+        self.proxy_signers.ecdsa_signers.remove(pubkey);
+        if let Some(keys) = self.proxy_pubkeys_ecdsa.get_mut(module_id) {
+            keys.retain(|key| key != pubkey);
+        }
+
+        Ok(())
+    }
+
     pub async fn create_proxy_bls(
         &mut self,
         module_id: ModuleId,
         delegator: BlsPublicKey,
+    ) -> Result<SignedProxyDelegationBls, SignerModuleError> {
+        self.create_versioned_proxy_bls(module_id, delegator, DelegationVersion::default()).await
+    }
+
+    pub async fn create_versioned_proxy_bls(
+        &mut self,
+        module_id: ModuleId,
+        delegator: BlsPublicKey,
+        version: DelegationVersion,
     ) -> Result<SignedProxyDelegationBls, SignerModuleError> {
         let signer = BlsSigner::new_random();
         let proxy_pubkey = signer.pubkey();
 
         let message = ProxyDelegationBls { delegator, proxy: proxy_pubkey };
-        let signature = self.sign_consensus(&delegator, &message.tree_hash_root().0).await?;
-        let delegation = SignedProxyDelegationBls { signature, message };
-        let proxy_signer = BlsProxySigner { signer, delegation };
+        let signature = self.sign_consensus(&delegator, &message.object_root(version)).await?;
+        let delegation = SignedProxyDelegationBls { signature, message, version };
+        let proxy_signer = BlsProxySigner { signer, delegation, created_at: Some(utcnow_sec()) };
 
         self.add_proxy_signer_bls(proxy_signer, module_id)
             .map_err(|err| SignerModuleError::Internal(err.to_string()))?;
@@ -109,14 +225,23 @@ impl SigningManager {
         &mut self,
         module_id: ModuleId,
         delegator: BlsPublicKey,
+    ) -> Result<SignedProxyDelegationEcdsa, SignerModuleError> {
+        self.create_versioned_proxy_ecdsa(module_id, delegator, DelegationVersion::default()).await
+    }
+
+    pub async fn create_versioned_proxy_ecdsa(
+        &mut self,
+        module_id: ModuleId,
+        delegator: BlsPublicKey,
+        version: DelegationVersion,
     ) -> Result<SignedProxyDelegationEcdsa, SignerModuleError> {
         let signer = EcdsaSigner::new_random();
         let proxy_pubkey = signer.pubkey();
 
         let message = ProxyDelegationEcdsa { delegator, proxy: proxy_pubkey };
-        let signature = self.sign_consensus(&delegator, &message.tree_hash_root().0).await?;
-        let delegation = SignedProxyDelegationEcdsa { signature, message };
-        let proxy_signer = EcdsaProxySigner { signer, delegation };
+        let signature = self.sign_consensus(&delegator, &message.object_root(version)).await?;
+        let delegation = SignedProxyDelegationEcdsa { signature, message, version };
+        let proxy_signer = EcdsaProxySigner { signer, delegation, created_at: Some(utcnow_sec()) };
 
         self.add_proxy_signer_ecdsa(proxy_signer, module_id)
             .map_err(|err| SignerModuleError::Internal(err.to_string()))?;
@@ -131,11 +256,76 @@ impl SigningManager {
         pubkey: &BlsPublicKey,
         object_root: &[u8; 32],
     ) -> Result<BlsSignature, SignerModuleError> {
+        if !self.is_consensus_allowed(pubkey) {
+            return Err(SignerModuleError::UnknownConsensusSigner(pubkey.to_vec()));
+        }
+
         let signer = self
             .consensus_signers
             .get(pubkey)
             .ok_or(SignerModuleError::UnknownConsensusSigner(pubkey.to_vec()))?;
         let signature = signer.sign(self.chain, *object_root).await;
+        let domain = compute_domain(self.chain, COMMIT_BOOST_DOMAIN);
+        self.check_bls_signature(pubkey, domain, object_root, &signature)?;
+
+        Ok(signature)
+    }
+
+    /// Signs a validator registration with the consensus key of the validator,
+    /// using the application builder domain
+    pub async fn sign_validator_registration(
+        &self,
+        message: &ValidatorRegistrationMessage,
+    ) -> Result<BlsSignature, SignerModuleError> {
+        let pubkey = &message.pubkey;
+        if !self.is_consensus_allowed(pubkey) {
+            return Err(SignerModuleError::UnknownConsensusSigner(pubkey.to_vec()));
+        }
+
+        let signer = self
+            .consensus_signers
+            .get(pubkey)
+            .ok_or(SignerModuleError::UnknownConsensusSigner(pubkey.to_vec()))?;
+        let signature = signer.sign_builder_msg(self.chain, message).await;
+        self.check_bls_signature(
+            pubkey,
+            self.chain.builder_domain(),
+            &message.tree_hash_root().0,
+            &signature,
+        )?;
+
+        Ok(signature)
+    }
+
+    /// Generates a proof of possession for a consensus key, or for a BLS proxy
+    /// of the module
+    pub async fn proof_of_possession(
+        &self,
+        module_id: &ModuleId,
+        pubkey: &BlsPublicKey,
+    ) -> Result<BlsSignature, SignerModuleError> {
+        let signer = if let Some(proxy) = self
+            .proxy_signers
+            .bls_signers
+            .get(pubkey)
+            .filter(|_| self.has_proxy_bls_for_module(pubkey, module_id))
+        {
+            &proxy.signer
+        } else if self.is_consensus_allowed(pubkey) {
+            self.consensus_signers
+                .get(pubkey)
+                .ok_or(SignerModuleError::UnknownConsensusSigner(pubkey.to_vec()))?
+        } else {
+            return Err(SignerModuleError::UnknownConsensusSigner(pubkey.to_vec()));
+        };
+
+        let signature = signer.proof_of_possession().await;
+        if self.verify_signatures {
+            verify_proof_of_possession(pubkey, &signature).map_err(|err| {
+                error!(%pubkey, %err, "Produced proof of possession failed verification");
+                SignerModuleError::Internal(format!("signature self-verification failed: {err}"))
+            })?;
+        }
 
         Ok(signature)
     }
@@ -151,6 +341,8 @@ impl SigningManager {
             .get(pubkey)
             .ok_or(SignerModuleError::UnknownProxySigner(pubkey.to_vec()))?;
         let signature = bls_proxy.sign(self.chain, *object_root).await;
+        let domain = compute_domain(self.chain, COMMIT_BOOST_DOMAIN);
+        self.check_bls_signature(pubkey, domain, object_root, &signature)?;
         Ok(signature)
     }
 
@@ -168,6 +360,32 @@ impl SigningManager {
         Ok(signature)
     }
 
+    /// Signing root of a module sign request, the same for every scheme
+    pub fn signing_root(&self, object_root: &[u8; 32]) -> [u8; 32] {
+        compute_signing_root(*object_root, compute_domain(self.chain, COMMIT_BOOST_DOMAIN))
+    }
+
+    /// Checks a signature we just produced, if enabled. A failure here means a
+    /// bug in the signing library or memory corruption
+    fn check_bls_signature(
+        &self,
+        pubkey: &BlsPublicKey,
+        domain: [u8; 32],
+        object_root: &[u8; 32],
+        signature: &BlsSignature,
+    ) -> Result<(), SignerModuleError> {
+        if !self.verify_signatures {
+            return Ok(());
+        }
+
+        let signing_root = compute_signing_root(*object_root, domain);
+
+        verify_bls_signature(pubkey, &signing_root, signature).map_err(|err| {
+            error!(%pubkey, %err, "Produced signature failed verification");
+            SignerModuleError::Internal(format!("signature self-verification failed: {err}"))
+        })
+    }
+
     pub fn consensus_pubkeys(&self) -> Vec<BlsPublicKey> {
         self.consensus_signers.keys().cloned().collect()
     }
@@ -184,6 +402,17 @@ impl SigningManager {
         self.consensus_signers.contains_key(pubkey)
     }
 
+    /// Whether the consensus key passes the allowlist, if one is configured
+    pub fn is_consensus_allowed(&self, pubkey: &BlsPublicKey) -> bool {
+        self.consensus_allowlist.as_ref().is_none_or(|allowlist| allowlist.contains(pubkey))
+    }
+
+    /// Number of consensus keys that can sign, keys left out by the allowlist
+    /// are not counted
+    pub fn allowed_consensus_count(&self) -> usize {
+        self.consensus_signers.keys().filter(|pubkey| self.is_consensus_allowed(pubkey)).count()
+    }
+
     pub fn has_proxy_bls_for_module(&self, bls_pk: &BlsPublicKey, module_id: &ModuleId) -> bool {
         match self.proxy_pubkeys_bls.get(module_id) {
             Some(keys) => keys.contains(bls_pk),
@@ -202,6 +431,27 @@ impl SigningManager {
         }
     }
 
+    /// Checks whether the module can sign with the key, either as a consensus
+    /// key or as one of its proxies
+    pub fn check_key(&self, module_id: &ModuleId, pubkey: ProxyPublicKey) -> KeyStatus {
+        let kind = match &pubkey {
+            ProxyPublicKey::Bls(pubkey)
+                if self.has_consensus(pubkey) && self.is_consensus_allowed(pubkey) =>
+            {
+                Some(KeyKind::Consensus)
+            }
+            ProxyPublicKey::Bls(pubkey) if self.has_proxy_bls_for_module(pubkey, module_id) => {
+                Some(KeyKind::ProxyBls)
+            }
+            ProxyPublicKey::Ecdsa(pubkey) if self.has_proxy_ecdsa_for_module(pubkey, module_id) => {
+                Some(KeyKind::ProxyEcdsa)
+            }
+            _ => None,
+        };
+
+        KeyStatus { pubkey, available: kind.is_some(), kind }
+    }
+
     pub fn get_delegation_bls(
         &self,
         pubkey: &BlsPublicKey,
@@ -228,33 +478,409 @@ impl SigningManager {
         &self,
         module_id: &ModuleId,
     ) -> eyre::Result<Vec<ConsensusProxyMap>> {
+        let (keys, orphans) = self.split_consensus_proxy_maps(module_id)?;
+
+        if let Some(proxy) = orphans.proxy_bls.first() {
+            bail!("missing consensus for proxy {proxy}");
+        }
+        if let Some(proxy) = orphans.proxy_ecdsa.first() {
+            bail!("missing consensus for proxy {proxy}");
+        }
+
+        Ok(keys)
+    }
+
+    /// Same as `get_consensus_proxy_maps`, but the proxies delegated by a
+    /// consensus key that is not loaded are returned apart instead of failing
+    pub fn split_consensus_proxy_maps(
+        &self,
+        module_id: &ModuleId,
+    ) -> eyre::Result<(Vec<ConsensusProxyMap>, OrphanProxies)> {
         let consensus = self.consensus_pubkeys();
         let proxy_bls = self.proxy_pubkeys_bls.get(module_id).cloned().unwrap_or_default();
         let proxy_ecdsa = self.proxy_pubkeys_ecdsa.get(module_id).cloned().unwrap_or_default();
 
         let mut keys: Vec<_> = consensus.into_iter().map(ConsensusProxyMap::new).collect();
+        let mut orphans = OrphanProxies::default();
 
         for bls in proxy_bls {
-            let delegator = self.get_delegation_bls(&bls)?.message.delegator;
-            let entry = keys
-                .iter_mut()
-                .find(|x| x.consensus == delegator)
-                .ok_or_eyre("missing consensus")?;
-
-            entry.proxy_bls.push(bls);
+            let proxy = self
+                .proxy_signers
+                .bls_signers
+                .get(&bls)
+                .ok_or(SignerModuleError::UnknownProxySigner(bls.as_ref().to_vec()))?;
+            match keys.iter_mut().find(|x| x.consensus == proxy.delegation.message.delegator) {
+                Some(entry) => {
+                    entry.proxy_bls.push(bls);
+                    if let Some(created_at) = proxy.created_at {
+                        entry.proxy_created_at.insert(bls.into(), created_at);
+                    }
+                }
+                None => orphans.proxy_bls.push(bls),
+            }
         }
 
         for ecdsa in proxy_ecdsa {
-            let delegator = self.get_delegation_ecdsa(&ecdsa)?.message.delegator;
-            let entry = keys
-                .iter_mut()
-                .find(|x| x.consensus == delegator)
-                .ok_or_eyre("missing consensus")?;
+            let proxy = self
+                .proxy_signers
+                .ecdsa_signers
+                .get(&ecdsa)
+                .ok_or(SignerModuleError::UnknownProxySigner(ecdsa.as_ref().to_vec()))?;
+            match keys.iter_mut().find(|x| x.consensus == proxy.delegation.message.delegator) {
+                Some(entry) => {
+                    entry.proxy_ecdsa.push(ecdsa);
+                    if let Some(created_at) = proxy.created_at {
+                        entry.proxy_created_at.insert(ecdsa.into(), created_at);
+                    }
+                }
+                None => orphans.proxy_ecdsa.push(ecdsa),
+            }
+        }
+
+        Ok((keys, orphans))
+    }
+
+    /// Returns the consensus/proxy maps of the given modules and of every
+    /// module that has proxies
+    pub fn get_all_consensus_proxy_maps<'a>(
+        &'a self,
+        module_ids: impl IntoIterator<Item = &'a ModuleId>,
+    ) -> eyre::Result<HashMap<ModuleId, Vec<ConsensusProxyMap>>> {
+        let module_ids: HashSet<&ModuleId> = module_ids
+            .into_iter()
+            .chain(self.proxy_pubkeys_bls.keys())
+            .chain(self.proxy_pubkeys_ecdsa.keys())
+            .collect();
+
+        module_ids
+            .into_iter()
+            .map(|module_id| Ok((module_id.clone(), self.get_consensus_proxy_maps(module_id)?)))
+            .collect()
+    }
 
-            entry.proxy_ecdsa.push(ecdsa);
+    /// Exports the proxies of every module, including their secret keys. They
+    /// must be sealed with a passphrase before leaving the signer
+    pub fn export_proxies(&self) -> ProxyBundleContents {
+        let proxies_bls = self
+            .proxy_pubkeys_bls
+            .iter()
+            .flat_map(|(module_id, pubkeys)| {
+                pubkeys.iter().filter_map(|pubkey| self.proxy_signers.bls_signers.get(pubkey)).map(
+                    |proxy| BundledProxy::<BlsPublicKey>::from_signer(module_id.clone(), proxy),
+                )
+            })
+            .collect();
+        let proxies_ecdsa = self
+            .proxy_pubkeys_ecdsa
+            .iter()
+            .flat_map(|(module_id, pubkeys)| {
+                pubkeys
+                    .iter()
+                    .filter_map(|pubkey| self.proxy_signers.ecdsa_signers.get(pubkey))
+                    .map(|proxy| {
+                        BundledProxy::<EcdsaPublicKey>::from_signer(module_id.clone(), proxy)
+                    })
+            })
+            .collect();
+
+        ProxyBundleContents {
+            chain: self.chain,
+            genesis_validators_root: B256::from(GENESIS_VALIDATORS_ROOT),
+            proxies_bls,
+            proxies_ecdsa,
         }
+    }
 
-        Ok(keys)
+    /// Imports the proxies of an opened bundle. All proxies are validated,
+    /// including that their keys don't collide with keys already in use, before
+    /// any is added, so an invalid bundle is never partially imported
+    pub fn import_proxies(
+        &mut self,
+        bundle: ProxyBundleContents,
+    ) -> Result<ImportedProxies, SignerModuleError> {
+        let invalid = |err: eyre::Report| SignerModuleError::InvalidRequest(err.to_string());
+
+        if bundle.chain.genesis_fork_version() != self.chain.genesis_fork_version() {
+            return Err(SignerModuleError::InvalidRequest(format!(
+                "bundle was exported for {}, but the signer is running on {}",
+                bundle.chain, self.chain
+            )));
+        }
+        let proxies_bls = bundle
+            .proxies_bls
+            .into_iter()
+            .map(|proxy| proxy.into_signer(self.chain))
+            .collect::<eyre::Result<Vec<_>>>()
+            .map_err(invalid)?;
+        let proxies_ecdsa = bundle
+            .proxies_ecdsa
+            .into_iter()
+            .map(|proxy| proxy.into_signer(self.chain))
+            .collect::<eyre::Result<Vec<_>>>()
+            .map_err(invalid)?;
+
+        let delegators = proxies_bls
+            .iter()
+            .map(|(_, proxy)| proxy.delegation.message.delegator)
+            .chain(proxies_ecdsa.iter().map(|(_, proxy)| proxy.delegation.message.delegator));
+        for delegator in delegators {
+            if !self.has_consensus(&delegator) || !self.is_consensus_allowed(&delegator) {
+                return Err(SignerModuleError::InvalidRequest(format!(
+                    "consensus key {delegator} of an imported proxy is not loaded or not allowed"
+                )));
+            }
+        }
+
+        // proxies already loaded for the same module are skipped, anything
+        // else that would be refused when adding them must be caught before
+        // the first is stored
+        let mut result = ImportedProxies::default();
+        let mut new_bls = HashSet::new();
+        let mut to_add_bls = Vec::new();
+        for (module_id, proxy) in proxies_bls {
+            let pubkey = proxy.pubkey();
+            if self.consensus_signers.contains_key(&pubkey) {
+                return Err(SignerModuleError::InvalidRequest(format!(
+                    "imported proxy {pubkey} is a loaded consensus key"
+                )));
+            }
+            if !new_bls.insert(pubkey) {
+                return Err(SignerModuleError::InvalidRequest(format!(
+                    "proxy {pubkey} is in the bundle more than once"
+                )));
+            }
+            if self.proxy_signers.bls_signers.contains_key(&pubkey) {
+                if !self.has_proxy_bls_for_module(&pubkey, &module_id) {
+                    return Err(SignerModuleError::InvalidRequest(format!(
+                        "imported proxy {pubkey} is already loaded for another module"
+                    )));
+                }
+                result.skipped += 1;
+                continue;
+            }
+            to_add_bls.push((module_id, proxy));
+        }
+
+        let mut new_ecdsa = HashSet::new();
+        let mut to_add_ecdsa = Vec::new();
+        for (module_id, proxy) in proxies_ecdsa {
+            let pubkey = proxy.pubkey();
+            if !new_ecdsa.insert(pubkey) {
+                return Err(SignerModuleError::InvalidRequest(format!(
+                    "proxy {pubkey} is in the bundle more than once"
+                )));
+            }
+            if self.proxy_signers.ecdsa_signers.contains_key(&pubkey) {
+                if !self.has_proxy_ecdsa_for_module(&pubkey, &module_id) {
+                    return Err(SignerModuleError::InvalidRequest(format!(
+                        "imported proxy {pubkey} is already loaded for another module"
+                    )));
+                }
+                result.skipped += 1;
+                continue;
+            }
+            to_add_ecdsa.push((module_id, proxy));
+        }
+
+        for (module_id, proxy) in to_add_bls {
+            self.add_proxy_signer_bls(proxy, module_id)
+                .map_err(|err| SignerModuleError::Internal(err.to_string()))?;
+            result.imported += 1;
+        }
+
+        for (module_id, proxy) in to_add_ecdsa {
+            self.add_proxy_signer_ecdsa(proxy, module_id)
+                .map_err(|err| SignerModuleError::Internal(err.to_string()))?;
+            result.imported += 1;
+        }
+
+        Ok(result)
+    }
+
+    /// Delegates every proxy of `old_consensus` to `new_consensus`, e.g. after
+    /// rotating a consensus key. All new delegations are signed and verified
+    /// before any is stored, and the old ones are removed only once all the
+    /// new ones are persisted. If storing one fails, the ones already stored
+    /// are undone
+    pub async fn redelegate_proxies(
+        &mut self,
+        old_consensus: &BlsPublicKey,
+        new_consensus: &BlsPublicKey,
+    ) -> Result<RedelegatedProxies, SignerModuleError> {
+        if old_consensus == new_consensus {
+            return Err(SignerModuleError::InvalidRequest(
+                "old and new consensus keys are the same".to_string(),
+            ));
+        }
+
+        let delegated_bls: Vec<(ModuleId, BlsPublicKey)> = self
+            .proxy_pubkeys_bls
+            .iter()
+            .flat_map(|(module_id, pubkeys)| pubkeys.iter().map(|pk| (module_id.clone(), *pk)))
+            .filter(|(_, pk)| {
+                self.proxy_signers
+                    .bls_signers
+                    .get(pk)
+                    .is_some_and(|proxy| proxy.delegation.message.delegator == *old_consensus)
+            })
+            .collect();
+        let delegated_ecdsa: Vec<(ModuleId, EcdsaPublicKey)> = self
+            .proxy_pubkeys_ecdsa
+            .iter()
+            .flat_map(|(module_id, pubkeys)| pubkeys.iter().map(|pk| (module_id.clone(), *pk)))
+            .filter(|(_, pk)| {
+                self.proxy_signers
+                    .ecdsa_signers
+                    .get(pk)
+                    .is_some_and(|proxy| proxy.delegation.message.delegator == *old_consensus)
+            })
+            .collect();
+
+        let mut result = RedelegatedProxies::default();
+
+        for (_, proxy) in &delegated_bls {
+            // keep the version of the old delegation, modules may only support that one
+            let version = self.proxy_signers.bls_signers[proxy].delegation.version;
+            let message = ProxyDelegationBls { delegator: *new_consensus, proxy: *proxy };
+            let signature =
+                self.sign_consensus(new_consensus, &message.object_root(version)).await?;
+            let delegation = SignedProxyDelegationBls { signature, message, version };
+            delegation.validate(self.chain).map_err(|err| {
+                SignerModuleError::Internal(format!("new delegation for {proxy} is invalid: {err}"))
+            })?;
+            result.proxies_bls.push(delegation);
+        }
+
+        for (_, proxy) in &delegated_ecdsa {
+            // keep the version of the old delegation, modules may only support that one
+            let version = self.proxy_signers.ecdsa_signers[proxy].delegation.version;
+            let message = ProxyDelegationEcdsa { delegator: *new_consensus, proxy: *proxy };
+            let signature =
+                self.sign_consensus(new_consensus, &message.object_root(version)).await?;
+            let delegation = SignedProxyDelegationEcdsa { signature, message, version };
+            delegation.validate(self.chain).map_err(|err| {
+                SignerModuleError::Internal(format!("new delegation for {proxy} is invalid: {err}"))
+            })?;
+            result.proxies_ecdsa.push(delegation);
+        }
+
+        let store_err = |err: eyre::Report| SignerModuleError::Internal(err.to_string());
+
+        let mut updated_bls = Vec::with_capacity(delegated_bls.len());
+        for ((module_id, pubkey), delegation) in delegated_bls.into_iter().zip(&result.proxies_bls)
+        {
+            let mut proxy = self.proxy_signers.bls_signers[&pubkey].clone();
+            let old_delegation = proxy.delegation;
+            proxy.delegation = *delegation;
+            if let Some(store) = &self.proxy_store {
+                if let Err(err) = store.store_proxy_bls(&module_id, &proxy) {
+                    self.undo_redelegation(&updated_bls, &[]);
+                    return Err(store_err(err));
+                }
+            }
+            updated_bls.push((module_id, old_delegation, proxy));
+        }
+
+        let mut updated_ecdsa = Vec::with_capacity(delegated_ecdsa.len());
+        for ((module_id, pubkey), delegation) in
+            delegated_ecdsa.into_iter().zip(&result.proxies_ecdsa)
+        {
+            let mut proxy = self.proxy_signers.ecdsa_signers[&pubkey].clone();
+            let old_delegation = proxy.delegation;
+            proxy.delegation = *delegation;
+            if let Some(store) = &self.proxy_store {
+                if let Err(err) = store.store_proxy_ecdsa(&module_id, &proxy) {
+                    self.undo_redelegation(&updated_bls, &updated_ecdsa);
+                    return Err(store_err(err));
+                }
+            }
+            updated_ecdsa.push((module_id, old_delegation, proxy));
+        }
+
+        // the new delegations are persisted, failing to clean up the old ones
+        // doesn't invalidate them
+        for (module_id, old_delegation, proxy) in updated_bls {
+            if let Some(store) = &self.proxy_store {
+                if let Err(err) = store.delete_stale_delegation(
+                    &module_id,
+                    &old_delegation.message,
+                    EncryptionScheme::Bls,
+                ) {
+                    warn!(proxy = %proxy.pubkey(), %err, "Failed to remove old proxy delegation");
+                }
+            }
+            self.proxy_signers.bls_signers.insert(proxy.pubkey(), proxy);
+        }
+
+        for (module_id, old_delegation, proxy) in updated_ecdsa {
+            if let Some(store) = &self.proxy_store {
+                if let Err(err) = store.delete_stale_delegation(
+                    &module_id,
+                    &old_delegation.message,
+                    EncryptionScheme::Ecdsa,
+                ) {
+                    warn!(proxy = %proxy.pubkey(), %err, "Failed to remove old proxy delegation");
+                }
+            }
+            self.proxy_signers.ecdsa_signers.insert(proxy.pubkey(), proxy);
+        }
+
+        Ok(result)
+    }
+
+    /// Removes the new delegations of proxies already stored by a redelegation
+    /// that failed, so the store only has the old ones again, as in memory
+    fn undo_redelegation(
+        &self,
+        updated_bls: &[(ModuleId, SignedProxyDelegationBls, BlsProxySigner)],
+        updated_ecdsa: &[(ModuleId, SignedProxyDelegationEcdsa, EcdsaProxySigner)],
+    ) {
+        let Some(store) = &self.proxy_store else {
+            return;
+        };
+
+        for (module_id, old_delegation, proxy) in updated_bls {
+            let undone = match store {
+                // the new delegation replaced the old one in the same file
+                ProxyStore::File { .. } => store.store_proxy_bls(module_id, &BlsProxySigner {
+                    delegation: *old_delegation,
+                    ..proxy.clone()
+                }),
+                ProxyStore::ERC2335 { .. } => store.delete_stale_delegation(
+                    module_id,
+                    &proxy.delegation.message,
+                    EncryptionScheme::Bls,
+                ),
+            };
+            if let Err(err) = undone {
+                error!(proxy = %proxy.pubkey(), %err, "Failed to undo proxy redelegation");
+            }
+        }
+
+        for (module_id, old_delegation, proxy) in updated_ecdsa {
+            let undone = match store {
+                ProxyStore::File { .. } => store.store_proxy_ecdsa(module_id, &EcdsaProxySigner {
+                    delegation: *old_delegation,
+                    ..proxy.clone()
+                }),
+                ProxyStore::ERC2335 { .. } => store.delete_stale_delegation(
+                    module_id,
+                    &proxy.delegation.message,
+                    EncryptionScheme::Ecdsa,
+                ),
+            };
+            if let Err(err) = undone {
+                error!(proxy = %proxy.pubkey(), %err, "Failed to undo proxy redelegation");
+            }
+        }
+    }
+
+    pub fn has_proxy_store(&self) -> bool {
+        self.proxy_store.is_some()
+    }
+
+    pub fn proxy_store(&self) -> Option<&ProxyStore> {
+        self.proxy_store.as_ref()
     }
 
     pub fn proxies(&self) -> &ProxySigners {
@@ -264,8 +890,11 @@ impl SigningManager {
 
 #[cfg(test)]
 mod tests {
-    use alloy::primitives::B256;
-    use cb_common::signature::compute_signing_root;
+    use alloy::primitives::{Address, B256};
+    use cb_common::{
+        constants::APPLICATION_BUILDER_DOMAIN,
+        signature::{compute_signing_root, verify_signed_message},
+    };
     use lazy_static::lazy_static;
 
     use super::*;
@@ -287,6 +916,485 @@ mod tests {
         (signing_manager, consensus_pk)
     }
 
+    mod test_consensus_allowlist {
+        use super::*;
+
+        #[tokio::test]
+        async fn test_allowlisted_key_signs() {
+            let (mut signing_manager, consensus_pk) = init_signing_manager();
+            signing_manager.set_consensus_allowlist([consensus_pk]);
+
+            let data_root = B256::random();
+            let result = signing_manager.sign_consensus(&consensus_pk, &data_root).await;
+
+            assert!(result.is_ok(), "Allowlisted consensus key must be able to sign.");
+        }
+
+        #[tokio::test]
+        async fn test_cleared_allowlist_allows_loaded_keys() {
+            let (mut signing_manager, consensus_pk) = init_signing_manager();
+            signing_manager.set_consensus_allowlist([ConsensusSigner::new_random().pubkey()]);
+            signing_manager.clear_consensus_allowlist();
+
+            let data_root = B256::random();
+            let result = signing_manager.sign_consensus(&consensus_pk, &data_root).await;
+
+            assert!(result.is_ok(), "Without allowlist, loaded consensus keys must sign.");
+        }
+
+        #[tokio::test]
+        async fn test_loaded_key_outside_allowlist_is_rejected() {
+            let (mut signing_manager, consensus_pk) = init_signing_manager();
+            signing_manager.set_consensus_allowlist([ConsensusSigner::new_random().pubkey()]);
+
+            let data_root = B256::random();
+            let result = signing_manager.sign_consensus(&consensus_pk, &data_root).await;
+
+            assert!(
+                matches!(result, Err(SignerModuleError::UnknownConsensusSigner(_))),
+                "Loaded consensus key outside the allowlist must not sign."
+            );
+            assert_eq!(signing_manager.allowed_consensus_count(), 0);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_verified_signatures_are_returned() {
+        let (mut signing_manager, consensus_pk) = init_signing_manager();
+        signing_manager.set_verify_signatures(true);
+
+        let signed_delegation =
+            signing_manager.create_proxy_bls(MODULE_ID.clone(), consensus_pk).await.unwrap();
+
+        let data_root = B256::random();
+        let consensus_sig = signing_manager.sign_consensus(&consensus_pk, &data_root).await;
+        let proxy_sig =
+            signing_manager.sign_proxy_bls(&signed_delegation.message.proxy, &data_root).await;
+
+        assert!(consensus_sig.is_ok(), "Valid consensus signature must pass verification.");
+        assert!(proxy_sig.is_ok(), "Valid proxy signature must pass verification.");
+    }
+
+    #[tokio::test]
+    async fn test_get_all_consensus_proxy_maps() {
+        let (mut signing_manager, consensus_pk) = init_signing_manager();
+        let other_module = ModuleId("OTHER_MODULE".to_string());
+        let idle_module = ModuleId("IDLE_MODULE".to_string());
+
+        let before = utcnow_sec();
+        let bls = signing_manager.create_proxy_bls(MODULE_ID.clone(), consensus_pk).await.unwrap();
+        signing_manager.create_proxy_ecdsa(other_module.clone(), consensus_pk).await.unwrap();
+
+        let maps = signing_manager.get_all_consensus_proxy_maps([&idle_module]).unwrap();
+
+        assert_eq!(maps.len(), 3);
+        assert_eq!(maps[&*MODULE_ID][0].proxy_bls.len(), 1);
+        assert_eq!(maps[&other_module][0].proxy_ecdsa.len(), 1);
+        assert!(maps[&idle_module][0].proxy_bls.is_empty());
+
+        let created_at =
+            maps[&*MODULE_ID][0].proxy_created_at[&ProxyPublicKey::from(bls.message.proxy)];
+        assert!(created_at >= before && created_at <= utcnow_sec());
+        assert!(maps[&idle_module][0].proxy_created_at.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_split_orphan_proxies() {
+        let (mut signing_manager, consensus_pk) = init_signing_manager();
+
+        let bls = signing_manager.create_proxy_bls(MODULE_ID.clone(), consensus_pk).await.unwrap();
+        let ecdsa =
+            signing_manager.create_proxy_ecdsa(MODULE_ID.clone(), consensus_pk).await.unwrap();
+
+        // the consensus key is removed, e.g. on reload
+        let other_signer = ConsensusSigner::new_random();
+        let other_pk = other_signer.pubkey();
+        signing_manager.replace_consensus_signers(vec![other_signer]);
+
+        let (maps, orphans) = signing_manager.split_consensus_proxy_maps(&MODULE_ID).unwrap();
+        assert_eq!(maps.len(), 1);
+        assert_eq!(maps[0].consensus, other_pk);
+        assert!(maps[0].proxy_bls.is_empty() && maps[0].proxy_ecdsa.is_empty());
+        assert_eq!(orphans.proxy_bls, vec![bls.message.proxy]);
+        assert_eq!(orphans.proxy_ecdsa, vec![ecdsa.message.proxy]);
+
+        let err = signing_manager.get_consensus_proxy_maps(&MODULE_ID).unwrap_err();
+        assert!(err.to_string().contains("missing consensus"));
+    }
+
+    #[tokio::test]
+    async fn test_versioned_proxy_delegation() {
+        let (mut signing_manager, consensus_pk) = init_signing_manager();
+
+        let v1 = signing_manager.create_proxy_bls(MODULE_ID.clone(), consensus_pk).await.unwrap();
+        let v2 = signing_manager
+            .create_versioned_proxy_bls(MODULE_ID.clone(), consensus_pk, DelegationVersion::V2)
+            .await
+            .unwrap();
+
+        assert_eq!(v1.version, DelegationVersion::V1);
+        assert_eq!(v2.version, DelegationVersion::V2);
+        assert!(v1.validate(CHAIN).is_ok());
+        assert!(v2.validate(CHAIN).is_ok());
+
+        // the signature only verifies against the root of its own version
+        let mismatched = SignedProxyDelegationBls { version: DelegationVersion::V1, ..v2 };
+        assert!(mismatched.validate(CHAIN).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_delete_proxy_only_for_owner() {
+        let (mut signing_manager, consensus_pk) = init_signing_manager();
+        let other_module = ModuleId("OTHER_MODULE".to_string());
+
+        let delegation =
+            signing_manager.create_proxy_bls(MODULE_ID.clone(), consensus_pk).await.unwrap();
+        let proxy_pk = delegation.message.proxy;
+
+        let err = signing_manager.delete_proxy_bls(&other_module, &proxy_pk).unwrap_err();
+        assert!(matches!(err, SignerModuleError::UnknownProxySigner(_)));
+
+        signing_manager.delete_proxy_bls(&MODULE_ID, &proxy_pk).unwrap();
+
+        assert!(!signing_manager.has_proxy_bls_for_module(&proxy_pk, &MODULE_ID));
+        assert!(signing_manager.sign_proxy_bls(&proxy_pk, &[0; 32]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_proxy_is_rejected() {
+        let (mut signing_manager, consensus_pk) = init_signing_manager();
+
+        let delegation =
+            signing_manager.create_proxy_bls(MODULE_ID.clone(), consensus_pk).await.unwrap();
+        let proxy = signing_manager.proxies().bls_signers[&delegation.message.proxy].clone();
+
+        assert!(signing_manager.add_proxy_signer_bls(proxy, MODULE_ID.clone()).is_err());
+        assert_eq!(signing_manager.proxy_pubkeys_bls()[&*MODULE_ID].len(), 1);
+    }
+
+    mod test_proxy_bundle {
+        use super::*;
+        use crate::bundle::ProxyBundle;
+
+        /// Returns the exported bundle and a manager without proxies but with
+        /// the same consensus key, to import it in
+        async fn export_bundle() -> (ProxyBundleContents, SigningManager) {
+            let mut signing_manager = SigningManager::new(CHAIN, None).unwrap();
+            let consensus_signer = ConsensusSigner::new_random();
+            let consensus_pk = consensus_signer.pubkey();
+            signing_manager.add_consensus_signer(consensus_signer.clone());
+
+            signing_manager.create_proxy_bls(MODULE_ID.clone(), consensus_pk).await.unwrap();
+            signing_manager.create_proxy_ecdsa(MODULE_ID.clone(), consensus_pk).await.unwrap();
+
+            let mut target = SigningManager::new(CHAIN, None).unwrap();
+            target.add_consensus_signer(consensus_signer);
+
+            (signing_manager.export_proxies(), target)
+        }
+
+        #[tokio::test]
+        async fn test_export_and_import() {
+            let (bundle, mut signing_manager) = export_bundle().await;
+            let proxy_pk = bundle.proxies_bls[0].delegation.message.proxy;
+
+            let result = signing_manager.import_proxies(bundle).unwrap();
+
+            assert_eq!(result.imported, 2);
+            assert_eq!(result.skipped, 0);
+            assert!(signing_manager.has_proxy_bls_for_module(&proxy_pk, &MODULE_ID));
+
+            // importing the same bundle again is a no-op
+            let bundle = signing_manager.export_proxies();
+            let result = signing_manager.import_proxies(bundle).unwrap();
+            assert_eq!(result.imported, 0);
+            assert_eq!(result.skipped, 2);
+        }
+
+        #[tokio::test]
+        async fn test_sealed_bundle_requires_passphrase() {
+            let (bundle, mut signing_manager) = export_bundle().await;
+            let sealed = serde_json::to_string(&bundle.seal(b"passphrase").unwrap()).unwrap();
+            assert!(!sealed.contains("secret"));
+            let parse = || serde_json::from_str::<ProxyBundle>(&sealed).unwrap();
+
+            assert!(parse().open(CHAIN, b"wrong passphrase").is_err());
+            let mut other_root = parse();
+            other_root.genesis_validators_root = B256::repeat_byte(1);
+            assert!(other_root.open(CHAIN, b"passphrase").is_err());
+
+            let bundle = parse().open(CHAIN, b"passphrase").unwrap();
+            let result = signing_manager.import_proxies(bundle).unwrap();
+            assert_eq!(result.imported, 2);
+        }
+
+        #[tokio::test]
+        async fn test_import_rejects_colliding_proxy() {
+            let (bundle, mut signing_manager) = export_bundle().await;
+            let proxy_signer = BlsSigner::new_from_bytes(&bundle.proxies_bls[0].secret).unwrap();
+            signing_manager.add_consensus_signer(proxy_signer);
+
+            let err = signing_manager.import_proxies(bundle).unwrap_err();
+
+            assert!(matches!(err, SignerModuleError::InvalidRequest(_)));
+            // nothing was imported, including the valid ecdsa proxy
+            assert!(signing_manager.proxies().ecdsa_signers.is_empty());
+        }
+
+        #[tokio::test]
+        async fn test_import_rejects_other_chain() {
+            let (mut bundle, mut signing_manager) = export_bundle().await;
+            bundle.chain = Chain::Mainnet;
+
+            let err = signing_manager.import_proxies(bundle).unwrap_err();
+
+            assert!(matches!(err, SignerModuleError::InvalidRequest(_)));
+        }
+
+        #[tokio::test]
+        async fn test_import_rejects_invalid_delegation() {
+            let (mut bundle, mut signing_manager) = export_bundle().await;
+            bundle.proxies_bls[0].delegation.signature = BlsSignature::default();
+
+            let err = signing_manager.import_proxies(bundle).unwrap_err();
+
+            assert!(matches!(err, SignerModuleError::InvalidRequest(_)));
+            // nothing was imported, including the valid ecdsa proxy
+            assert!(signing_manager.proxies().ecdsa_signers.is_empty());
+        }
+
+        #[tokio::test]
+        async fn test_import_requires_consensus_key() {
+            let (bundle, _) = export_bundle().await;
+
+            let mut signing_manager = SigningManager::new(CHAIN, None).unwrap();
+            let err = signing_manager.import_proxies(bundle).unwrap_err();
+
+            assert!(matches!(err, SignerModuleError::InvalidRequest(_)));
+        }
+
+        #[tokio::test]
+        async fn test_import_requires_allowed_consensus_key() {
+            let (bundle, mut signing_manager) = export_bundle().await;
+            signing_manager.set_consensus_allowlist(Vec::new());
+
+            let err = signing_manager.import_proxies(bundle).unwrap_err();
+
+            assert!(matches!(err, SignerModuleError::InvalidRequest(_)));
+            assert!(signing_manager.proxies().bls_signers.is_empty());
+        }
+
+        #[tokio::test]
+        async fn test_import_rejects_proxy_of_other_module() {
+            let (bundle, mut signing_manager) = export_bundle().await;
+            signing_manager.import_proxies(bundle).unwrap();
+
+            let mut moved = signing_manager.export_proxies();
+            moved.proxies_ecdsa[0].module_id = ModuleId("OTHER_MODULE".to_string());
+            let err = signing_manager.import_proxies(moved).unwrap_err();
+
+            assert!(matches!(err, SignerModuleError::InvalidRequest(_)));
+            assert!(signing_manager
+                .proxy_pubkeys_ecdsa()
+                .get(&ModuleId("OTHER_MODULE".to_string()))
+                .is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sign_validator_registration() {
+        let (mut signing_manager, consensus_pk) = init_signing_manager();
+        signing_manager.set_verify_signatures(true);
+
+        let message = ValidatorRegistrationMessage {
+            fee_recipient: Address::repeat_byte(1),
+            gas_limit: 30_000_000,
+            timestamp: 1_716_391_930,
+            pubkey: consensus_pk,
+        };
+
+        let signature = signing_manager.sign_validator_registration(&message).await.unwrap();
+
+        assert!(verify_signed_message(
+            CHAIN,
+            &consensus_pk,
+            &message,
+            &signature,
+            APPLICATION_BUILDER_DOMAIN
+        )
+        .is_ok());
+        // registrations are not valid as commit-boost signatures
+        assert!(verify_signed_message(
+            CHAIN,
+            &consensus_pk,
+            &message,
+            &signature,
+            COMMIT_BOOST_DOMAIN
+        )
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_redelegate_proxies() {
+        let (mut signing_manager, old_pk) = init_signing_manager();
+        let new_signer = ConsensusSigner::new_random();
+        let new_pk = new_signer.pubkey();
+        signing_manager.add_consensus_signer(new_signer);
+
+        let bls = signing_manager.create_proxy_bls(MODULE_ID.clone(), old_pk).await.unwrap();
+        let ecdsa = signing_manager
+            .create_versioned_proxy_ecdsa(MODULE_ID.clone(), old_pk, DelegationVersion::V2)
+            .await
+            .unwrap();
+        let untouched = signing_manager.create_proxy_bls(MODULE_ID.clone(), new_pk).await.unwrap();
+
+        let result = signing_manager.redelegate_proxies(&old_pk, &new_pk).await.unwrap();
+
+        assert_eq!(result.proxies_bls.len(), 1);
+        assert_eq!(result.proxies_ecdsa.len(), 1);
+
+        let new_bls = signing_manager.get_delegation_bls(&bls.message.proxy).unwrap();
+        let new_ecdsa = signing_manager.get_delegation_ecdsa(&ecdsa.message.proxy).unwrap();
+        assert_eq!(new_bls.message.delegator, new_pk);
+        assert_eq!(new_ecdsa.message.delegator, new_pk);
+        assert!(new_bls.validate(CHAIN).is_ok());
+        assert!(new_ecdsa.validate(CHAIN).is_ok());
+        assert_eq!(new_ecdsa.version, DelegationVersion::V2);
+
+        let unchanged = signing_manager.get_delegation_bls(&untouched.message.proxy).unwrap();
+        assert_eq!(unchanged.signature, untouched.signature);
+
+        let maps = signing_manager.get_consensus_proxy_maps(&MODULE_ID).unwrap();
+        let old_map = maps.iter().find(|map| map.consensus == old_pk).unwrap();
+        assert!(old_map.proxy_bls.is_empty() && old_map.proxy_ecdsa.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_redelegate_undoes_stored_proxies_on_failure() {
+        let proxy_dir =
+            std::env::temp_dir().join("test_redelegate_undoes_stored_proxies_on_failure");
+        let _ = std::fs::remove_dir_all(&proxy_dir);
+        std::fs::create_dir_all(&proxy_dir).unwrap();
+        let store = ProxyStore::File { proxy_dir: proxy_dir.clone(), encrypted: false };
+        let mut signing_manager = SigningManager::new(CHAIN, Some(store)).unwrap();
+        let old_signer = ConsensusSigner::new_random();
+        let old_pk = old_signer.pubkey();
+        let new_signer = ConsensusSigner::new_random();
+        let new_pk = new_signer.pubkey();
+        signing_manager.add_consensus_signer(old_signer);
+        signing_manager.add_consensus_signer(new_signer);
+
+        let first = signing_manager.create_proxy_bls(MODULE_ID.clone(), old_pk).await.unwrap();
+        let second = signing_manager.create_proxy_bls(MODULE_ID.clone(), old_pk).await.unwrap();
+
+        // the second proxy can't be stored again, after the first one was
+        let bls_dir = proxy_dir.join(MODULE_ID.to_string()).join("bls");
+        let second_path = bls_dir.join(second.message.proxy.to_string());
+        std::fs::remove_file(&second_path).unwrap();
+        std::fs::create_dir(&second_path).unwrap();
+
+        let result = signing_manager.redelegate_proxies(&old_pk, &new_pk).await;
+        assert!(matches!(result, Err(SignerModuleError::Internal(_))));
+
+        let stored: serde_json::Value = serde_json::from_slice(
+            &std::fs::read(bls_dir.join(first.message.proxy.to_string())).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(stored["delegation"]["message"]["delegator"], old_pk.to_string());
+        let delegation = signing_manager.get_delegation_bls(&first.message.proxy).unwrap();
+        assert_eq!(delegation.message.delegator, old_pk);
+    }
+
+    #[tokio::test]
+    async fn test_redelegate_requires_new_consensus_key() {
+        let (mut signing_manager, old_pk) = init_signing_manager();
+        let bls = signing_manager.create_proxy_bls(MODULE_ID.clone(), old_pk).await.unwrap();
+
+        let new_pk = ConsensusSigner::new_random().pubkey();
+        let result = signing_manager.redelegate_proxies(&old_pk, &new_pk).await;
+
+        assert!(matches!(result, Err(SignerModuleError::UnknownConsensusSigner(_))));
+        let delegation = signing_manager.get_delegation_bls(&bls.message.proxy).unwrap();
+        assert_eq!(delegation.message.delegator, old_pk);
+    }
+
+    #[tokio::test]
+    async fn test_proof_of_possession() {
+        let (mut signing_manager, consensus_pk) = init_signing_manager();
+        signing_manager.set_verify_signatures(true);
+        let proxy =
+            signing_manager.create_proxy_bls(MODULE_ID.clone(), consensus_pk).await.unwrap();
+        let proxy_pk = proxy.message.proxy;
+
+        let consensus_pop =
+            signing_manager.proof_of_possession(&MODULE_ID, &consensus_pk).await.unwrap();
+        let proxy_pop = signing_manager.proof_of_possession(&MODULE_ID, &proxy_pk).await.unwrap();
+
+        assert!(verify_proof_of_possession(&consensus_pk, &consensus_pop).is_ok());
+        assert!(verify_proof_of_possession(&proxy_pk, &proxy_pop).is_ok());
+        assert!(verify_proof_of_possession(&proxy_pk, &consensus_pop).is_err());
+        // a proof of possession is not a valid signature of the pubkey as a message
+        assert!(
+            verify_bls_signature(&consensus_pk, consensus_pk.as_slice(), &consensus_pop).is_err()
+        );
+
+        let other_module = ModuleId("OTHER_MODULE".to_string());
+        let result = signing_manager.proof_of_possession(&other_module, &proxy_pk).await;
+        assert!(matches!(result, Err(SignerModuleError::UnknownConsensusSigner(_))));
+    }
+
+    #[tokio::test]
+    async fn test_signing_root() {
+        let (signing_manager, consensus_pk) = init_signing_manager();
+
+        let data_root = B256::random();
+        let signature = signing_manager.sign_consensus(&consensus_pk, &data_root).await.unwrap();
+        let signing_root = signing_manager.signing_root(&data_root);
+
+        assert!(verify_bls_signature(&consensus_pk, &signing_root, &signature).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_check_key() {
+        let (mut signing_manager, consensus_pk) = init_signing_manager();
+        let other_module = ModuleId("OTHER_MODULE".to_string());
+
+        let delegation =
+            signing_manager.create_proxy_ecdsa(MODULE_ID.clone(), consensus_pk).await.unwrap();
+        let proxy_pk = ProxyPublicKey::Ecdsa(delegation.message.proxy);
+
+        let status = signing_manager.check_key(&MODULE_ID, consensus_pk.into());
+        assert_eq!(status.kind, Some(KeyKind::Consensus));
+
+        let status = signing_manager.check_key(&MODULE_ID, proxy_pk);
+        assert_eq!(status.kind, Some(KeyKind::ProxyEcdsa));
+
+        // proxies are only available to the module that owns them
+        let status = signing_manager.check_key(&other_module, proxy_pk);
+        assert!(!status.available);
+
+        let unknown: BlsPublicKey = BlsSigner::new_random().pubkey();
+        assert!(!signing_manager.check_key(&MODULE_ID, unknown.into()).available);
+    }
+
+    #[tokio::test]
+    async fn test_replace_consensus_signers_keeps_proxies() {
+        let (mut signing_manager, consensus_pk) = init_signing_manager();
+
+        let signed_delegation =
+            signing_manager.create_proxy_bls(MODULE_ID.clone(), consensus_pk).await.unwrap();
+
+        let new_signer = ConsensusSigner::new_random();
+        let new_pk = new_signer.pubkey();
+        signing_manager.replace_consensus_signers(vec![new_signer]);
+
+        assert_eq!(signing_manager.consensus_pubkeys(), vec![new_pk]);
+        assert!(
+            signing_manager.has_proxy_bls_for_module(&signed_delegation.message.proxy, &MODULE_ID),
+            "Proxies must survive a reload of the consensus keys."
+        );
+    }
+
     mod test_proxy_bls {
         use cb_common::{
             constants::COMMIT_BOOST_DOMAIN, signature::compute_domain, signer::verify_bls_signature,
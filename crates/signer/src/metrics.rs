@@ -2,13 +2,28 @@
 
 use axum::http::Uri;
 use cb_common::commit::constants::{
-    GENERATE_PROXY_KEY_PATH, GET_PUBKEYS_PATH, REQUEST_SIGNATURE_PATH,
+    CHECK_KEYS_PATH, DELETE_PROXY_KEYS_PATH, GENERATE_PROXY_KEY_PATH, GET_PUBKEYS_PATH,
+    PERMISSIONS_PATH, PROOF_OF_POSSESSION_PATH, REQUEST_EXAMPLES_PATH, REQUEST_SIGNATURE_PATH,
+    SIGNER_ALL_PUBKEYS_PATH, SIGNER_CONFIG_PATH, SIGNER_EXPORT_PROXIES_PATH,
+    SIGNER_IMPORT_PROXIES_PATH, SIGNER_KEY_USAGE_PATH, SIGNER_MODULE_DENYLIST_PATH,
+    SIGNER_REDELEGATE_PROXIES_PATH, SIGNER_REENCRYPT_KEYSTORES_PATH, SIGNER_RELOAD_PATH,
+    SIGNER_STATS_PATH, SIGN_VALIDATOR_REGISTRATION_PATH,
 };
 use lazy_static::lazy_static;
-use prometheus::{register_int_counter_vec_with_registry, IntCounterVec, Registry};
+use prometheus::{
+    register_histogram_vec_with_registry, register_int_counter_vec_with_registry,
+    register_int_gauge_vec_with_registry, HistogramVec, IntCounterVec, IntGaugeVec, Registry,
+};
 
 use crate::constants::{
-    GENERATE_PROXY_KEY_ENDPOINT_TAG, GET_PUBKEYS_ENDPOINT_TAG, REQUEST_SIGNATURE_ENDPOINT_TAG,
+    CHECK_KEYS_ENDPOINT_TAG, DELETE_PROXY_KEYS_ENDPOINT_TAG, GENERATE_PROXY_KEY_ENDPOINT_TAG,
+    GET_PUBKEYS_ENDPOINT_TAG, PERMISSIONS_ENDPOINT_TAG, PROOF_OF_POSSESSION_ENDPOINT_TAG,
+    REQUEST_EXAMPLES_ENDPOINT_TAG, REQUEST_SIGNATURE_ENDPOINT_TAG, SIGNER_ALL_PUBKEYS_ENDPOINT_TAG,
+    SIGNER_CONFIG_ENDPOINT_TAG, SIGNER_EXPORT_PROXIES_ENDPOINT_TAG,
+    SIGNER_IMPORT_PROXIES_ENDPOINT_TAG, SIGNER_KEY_USAGE_ENDPOINT_TAG,
+    SIGNER_MODULE_DENYLIST_ENDPOINT_TAG, SIGNER_REDELEGATE_PROXIES_ENDPOINT_TAG,
+    SIGNER_REENCRYPT_KEYSTORES_ENDPOINT_TAG, SIGNER_RELOAD_ENDPOINT_TAG, SIGNER_STATS_ENDPOINT_TAG,
+    SIGN_VALIDATOR_REGISTRATION_ENDPOINT_TAG,
 };
 
 lazy_static! {
@@ -22,13 +37,122 @@ lazy_static! {
         &["http_status_code", "endpoint"],
         SIGNER_METRICS_REGISTRY
     ).unwrap();
+
+    /// Requests for a consensus key that is not loaded or not allowed
+    pub static ref SIGNER_UNKNOWN_CONSENSUS_SIGNER: IntCounterVec = register_int_counter_vec_with_registry!(
+        "signer_unknown_consensus_signer_total",
+        "Requests for an unknown consensus signer, by module",
+        &["module_id"],
+        SIGNER_METRICS_REGISTRY
+    ).unwrap();
+
+    /// Requests for a proxy key that is not loaded or belongs to another module
+    pub static ref SIGNER_UNKNOWN_PROXY_SIGNER: IntCounterVec = register_int_counter_vec_with_registry!(
+        "signer_unknown_proxy_signer_total",
+        "Requests for an unknown proxy signer, by module",
+        &["module_id"],
+        SIGNER_METRICS_REGISTRY
+    ).unwrap();
+
+    /// Requests rejected because of a missing or invalid JWT. The module is not
+    /// known at this point, so this is labeled by endpoint
+    pub static ref SIGNER_UNAUTHORIZED: IntCounterVec = register_int_counter_vec_with_registry!(
+        "signer_unauthorized_total",
+        "Requests with an invalid JWT, by endpoint",
+        &["endpoint"],
+        SIGNER_METRICS_REGISTRY
+    ).unwrap();
+
+    /// Sign requests dropped before completion, usually because the module
+    /// disconnected
+    pub static ref SIGNER_CANCELLED_REQUESTS: IntCounterVec = register_int_counter_vec_with_registry!(
+        "signer_cancelled_requests_total",
+        "Sign requests cancelled before completion, by module",
+        &["module_id"],
+        SIGNER_METRICS_REGISTRY
+    ).unwrap();
+
+    /// Sign requests that exceeded the configured timeout
+    pub static ref SIGNER_TIMED_OUT_REQUESTS: IntCounterVec = register_int_counter_vec_with_registry!(
+        "signer_timed_out_requests_total",
+        "Sign requests that timed out, by module",
+        &["module_id"],
+        SIGNER_METRICS_REGISTRY
+    ).unwrap();
+
+    /// Signatures used by each module in the current quota window
+    pub static ref SIGNER_QUOTA_USED: IntGaugeVec = register_int_gauge_vec_with_registry!(
+        "signer_quota_used",
+        "Signatures requested by module in the current quota window",
+        &["module_id"],
+        SIGNER_METRICS_REGISTRY
+    ).unwrap();
+
+    /// Latency of the sign requests for a slot, by the phase of the slot the
+    /// request arrived in
+    pub static ref SIGNER_SIGN_LATENCY: HistogramVec = register_histogram_vec_with_registry!(
+        "signer_sign_latency",
+        "Sign latency by slot phase",
+        &["slot_phase"],
+        SIGNER_METRICS_REGISTRY
+    ).unwrap();
+
+    /// Proxies of each module whose consensus key is not loaded, updated on
+    /// get_pubkeys when orphan proxies are reported
+    pub static ref SIGNER_ORPHAN_PROXIES: IntGaugeVec = register_int_gauge_vec_with_registry!(
+        "signer_orphan_proxies",
+        "Proxies whose consensus key is not loaded, by module",
+        &["module_id"],
+        SIGNER_METRICS_REGISTRY
+    ).unwrap();
+}
+
+/// Splits the slot in thirds. Requests arriving before the slot starts count
+/// as early, the ones arriving after it ended as past
+pub fn slot_phase(ms_into_slot: u64, slot_time_ms: u64) -> &'static str {
+    match ms_into_slot * 3 / slot_time_ms.max(1) {
+        0 => "early",
+        1 => "mid",
+        2 => "late",
+        _ => "past",
+    }
 }
 
 pub fn uri_to_tag(uri: &Uri) -> &str {
     match uri.path() {
         GET_PUBKEYS_PATH => GET_PUBKEYS_ENDPOINT_TAG,
         GENERATE_PROXY_KEY_PATH => GENERATE_PROXY_KEY_ENDPOINT_TAG,
+        DELETE_PROXY_KEYS_PATH => DELETE_PROXY_KEYS_ENDPOINT_TAG,
+        CHECK_KEYS_PATH => CHECK_KEYS_ENDPOINT_TAG,
+        PROOF_OF_POSSESSION_PATH => PROOF_OF_POSSESSION_ENDPOINT_TAG,
+        REQUEST_EXAMPLES_PATH => REQUEST_EXAMPLES_ENDPOINT_TAG,
+        PERMISSIONS_PATH => PERMISSIONS_ENDPOINT_TAG,
         REQUEST_SIGNATURE_PATH => REQUEST_SIGNATURE_ENDPOINT_TAG,
+        SIGN_VALIDATOR_REGISTRATION_PATH => SIGN_VALIDATOR_REGISTRATION_ENDPOINT_TAG,
+        SIGNER_CONFIG_PATH => SIGNER_CONFIG_ENDPOINT_TAG,
+        SIGNER_RELOAD_PATH => SIGNER_RELOAD_ENDPOINT_TAG,
+        SIGNER_ALL_PUBKEYS_PATH => SIGNER_ALL_PUBKEYS_ENDPOINT_TAG,
+        SIGNER_EXPORT_PROXIES_PATH => SIGNER_EXPORT_PROXIES_ENDPOINT_TAG,
+        SIGNER_IMPORT_PROXIES_PATH => SIGNER_IMPORT_PROXIES_ENDPOINT_TAG,
+        SIGNER_REDELEGATE_PROXIES_PATH => SIGNER_REDELEGATE_PROXIES_ENDPOINT_TAG,
+        SIGNER_MODULE_DENYLIST_PATH => SIGNER_MODULE_DENYLIST_ENDPOINT_TAG,
+        SIGNER_KEY_USAGE_PATH => SIGNER_KEY_USAGE_ENDPOINT_TAG,
+        SIGNER_REENCRYPT_KEYSTORES_PATH => SIGNER_REENCRYPT_KEYSTORES_ENDPOINT_TAG,
+        SIGNER_STATS_PATH => SIGNER_STATS_ENDPOINT_TAG,
         _ => "unknown endpoint",
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slot_phase() {
+        assert_eq!(slot_phase(0, 12_000), "early");
+        assert_eq!(slot_phase(3_999, 12_000), "early");
+        assert_eq!(slot_phase(4_000, 12_000), "mid");
+        assert_eq!(slot_phase(11_999, 12_000), "late");
+        assert_eq!(slot_phase(12_000, 12_000), "past");
+    }
+}
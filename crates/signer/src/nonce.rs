@@ -0,0 +1,253 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use cb_common::types::ModuleId;
+use eyre::Context;
+use tracing::error;
+
+use crate::error::SignerModuleError;
+
+/// Sign request nonces seen for each module. Nonces must strictly increase,
+/// unless a window is set: then any unused nonce among the last `window` ones
+/// is also accepted once, for concurrent requests arriving out of order. Every
+/// accepted nonce is persisted before the request is signed, so a captured
+/// request can't be replayed even after a restart
+pub struct NonceTracker {
+    path: PathBuf,
+    window: usize,
+    state: Mutex<NonceState>,
+    /// Generation of the last persisted state. Held while writing the file, so
+    /// requests arriving during a write are persisted together by the next one
+    persisted: tokio::sync::Mutex<u64>,
+}
+
+struct NonceState {
+    modules: HashMap<ModuleId, ModuleNonces>,
+    /// Incremented on every accepted nonce
+    generation: u64,
+}
+
+#[derive(Default, Clone)]
+struct ModuleNonces {
+    /// Nonces up to this one are rejected
+    floor: u64,
+    /// Accepted nonces above the floor, at most the window
+    recent: BTreeSet<u64>,
+}
+
+impl ModuleNonces {
+    fn highest(&self) -> u64 {
+        self.recent.last().copied().unwrap_or(self.floor)
+    }
+
+    fn accept(&mut self, nonce: u64, window: usize) -> Result<(), SignerModuleError> {
+        if nonce <= self.floor {
+            return Err(SignerModuleError::InvalidNonce(format!(
+                "nonce {nonce} is too old, it must be higher than {}",
+                self.floor
+            )));
+        }
+        if !self.recent.insert(nonce) {
+            return Err(SignerModuleError::InvalidNonce(format!("nonce {nonce} was already used")));
+        }
+        while self.recent.len() > window {
+            if let Some(oldest) = self.recent.pop_first() {
+                self.floor = oldest;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl NonceTracker {
+    /// Restores the nonces saved in the file, starting with none if it doesn't
+    /// exist yet. Only the highest nonce of each module is saved, so after a
+    /// restart nonces must be higher than it
+    pub fn load(path: PathBuf, window: usize) -> eyre::Result<Self> {
+        let highest: HashMap<ModuleId, u64> = match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .wrap_err_with(|| format!("invalid nonces file {path:?}"))?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(err) => {
+                return Err(err).wrap_err_with(|| format!("failed to read nonces file {path:?}"))
+            }
+        };
+
+        let modules = highest
+            .into_iter()
+            .map(|(module_id, floor)| (module_id, ModuleNonces { floor, recent: BTreeSet::new() }))
+            .collect();
+
+        Ok(Self {
+            path,
+            window,
+            state: Mutex::new(NonceState { modules, generation: 0 }),
+            persisted: tokio::sync::Mutex::new(0),
+        })
+    }
+
+    /// Accepts the nonce only if it's higher than the previous one of the
+    /// module, or in its window and unused, once it's persisted
+    pub async fn check(
+        &self,
+        module_id: &ModuleId,
+        nonce: Option<u64>,
+    ) -> Result<(), SignerModuleError> {
+        let Some(nonce) = nonce else {
+            return Err(SignerModuleError::InvalidNonce("missing or invalid nonce".to_string()));
+        };
+
+        let (generation, previous, floor) = {
+            let mut state = self.state.lock().expect("nonce lock poisoned");
+            let module = state.modules.entry(module_id.clone()).or_default();
+            let previous = module.clone();
+            module.accept(nonce, self.window)?;
+            let floor = module.floor;
+            state.generation += 1;
+            (state.generation, previous, floor)
+        };
+
+        if let Err(err) = self.persist_up_to(generation).await {
+            error!(%err, path = ?self.path, "Failed to persist sign request nonces");
+            // the nonce can only be accepted once it's persisted, so the floor
+            // it raised is restored too, unless a later nonce raised it again
+            let mut state = self.state.lock().expect("nonce lock poisoned");
+            if let Some(module) = state.modules.get_mut(module_id) {
+                module.recent.remove(&nonce);
+                if module.floor == floor && floor != previous.floor {
+                    let evicted = previous.recent.range(..=floor).copied();
+                    module.recent.extend(evicted);
+                    module.floor = previous.floor;
+                }
+            }
+            return Err(SignerModuleError::Internal("failed to persist nonce".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Persists the state if it wasn't already persisted by a later write
+    async fn persist_up_to(&self, generation: u64) -> eyre::Result<()> {
+        let mut persisted = self.persisted.lock().await;
+        if *persisted >= generation {
+            return Ok(());
+        }
+
+        let (highest, latest) = {
+            let state = self.state.lock().expect("nonce lock poisoned");
+            let highest: HashMap<ModuleId, u64> = state
+                .modules
+                .iter()
+                .map(|(module_id, nonces)| (module_id.clone(), nonces.highest()))
+                .collect();
+            (highest, state.generation)
+        };
+
+        let path = self.path.clone();
+        tokio::task::spawn_blocking(move || persist(&path, &highest)).await??;
+        *persisted = latest;
+
+        Ok(())
+    }
+}
+
+/// Replaces the nonces file atomically, so a crash never leaves it half written
+fn persist(path: &Path, highest: &HashMap<ModuleId, u64>) -> eyre::Result<()> {
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+
+    std::fs::write(&tmp_path, serde_json::to_vec(highest)?)?;
+    std::fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_nonces_reject_replays_across_restarts() {
+        let tmp_path = std::env::temp_dir().join("test_nonces_reject_replays_across_restarts");
+        let _ = std::fs::remove_dir_all(&tmp_path);
+        std::fs::create_dir_all(&tmp_path).unwrap();
+        let path = tmp_path.join("signer.nonces");
+        let module_a = ModuleId("MODULE_A".to_string());
+        let module_b = ModuleId("MODULE_B".to_string());
+
+        let tracker = NonceTracker::load(path.clone(), 8).unwrap();
+        assert!(tracker.check(&module_a, None).await.is_err());
+        assert!(tracker.check(&module_a, Some(5)).await.is_ok());
+        assert!(tracker.check(&module_a, Some(5)).await.is_err());
+        // concurrent requests can arrive out of order
+        assert!(tracker.check(&module_a, Some(4)).await.is_ok());
+        assert!(tracker.check(&module_a, Some(4)).await.is_err());
+        // nonces are tracked per module
+        assert!(tracker.check(&module_b, Some(1)).await.is_ok());
+
+        let restored = NonceTracker::load(path, 8).unwrap();
+        assert!(restored.check(&module_a, Some(5)).await.is_err());
+        assert!(restored.check(&module_a, Some(4)).await.is_err());
+        assert!(restored.check(&module_a, Some(6)).await.is_ok());
+        assert!(restored.check(&module_b, Some(1)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_nonces_older_than_window_are_rejected() {
+        let tmp_path = std::env::temp_dir().join("test_nonces_older_than_window_are_rejected");
+        let _ = std::fs::remove_dir_all(&tmp_path);
+        std::fs::create_dir_all(&tmp_path).unwrap();
+        let module_id = ModuleId("MODULE".to_string());
+
+        let window = 8;
+        let tracker = NonceTracker::load(tmp_path.join("signer.nonces"), window).unwrap();
+        for nonce in 10..10 + window as u64 + 1 {
+            assert!(tracker.check(&module_id, Some(nonce)).await.is_ok());
+        }
+
+        // the first nonce left the window, a late request with a lower unused
+        // nonce is rejected
+        assert!(tracker.check(&module_id, Some(9)).await.is_err());
+        assert!(tracker.check(&module_id, Some(10)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_nonces_strictly_increase_without_window() {
+        let tmp_path = std::env::temp_dir().join("test_nonces_strictly_increase_without_window");
+        let _ = std::fs::remove_dir_all(&tmp_path);
+        std::fs::create_dir_all(&tmp_path).unwrap();
+        let module_id = ModuleId("MODULE".to_string());
+
+        let tracker = NonceTracker::load(tmp_path.join("signer.nonces"), 0).unwrap();
+        assert!(tracker.check(&module_id, Some(5)).await.is_ok());
+        assert!(tracker.check(&module_id, Some(4)).await.is_err());
+        assert!(tracker.check(&module_id, Some(5)).await.is_err());
+        assert!(tracker.check(&module_id, Some(6)).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_nonce_not_persisted_is_undone() {
+        let tmp_path = std::env::temp_dir().join("test_nonce_not_persisted_is_undone");
+        let _ = std::fs::remove_dir_all(&tmp_path);
+        std::fs::create_dir_all(&tmp_path).unwrap();
+        let path = tmp_path.join("signer.nonces");
+        let module_id = ModuleId("MODULE".to_string());
+
+        let tracker = NonceTracker::load(path.clone(), 0).unwrap();
+        assert!(tracker.check(&module_id, Some(5)).await.is_ok());
+
+        // a directory in the way of the temp file makes the write fail
+        let blocker = tmp_path.join("signer.nonces.tmp");
+        std::fs::create_dir(&blocker).unwrap();
+        assert!(tracker.check(&module_id, Some(10)).await.is_err());
+        std::fs::remove_dir(&blocker).unwrap();
+
+        // the rejected nonce didn't raise the high-water mark
+        assert!(tracker.check(&module_id, Some(6)).await.is_ok());
+        let restored = NonceTracker::load(path, 0).unwrap();
+        assert!(restored.check(&module_id, Some(7)).await.is_ok());
+    }
+}
@@ -0,0 +1,144 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use cb_common::{config::SignatureQuota, types::ModuleId};
+
+use crate::{error::SignerModuleError, metrics::SIGNER_QUOTA_USED};
+
+/// Tracks how many signatures each module requested in the current window
+pub struct SignatureQuotas {
+    max_signatures: u64,
+    window: Duration,
+    windows: Mutex<HashMap<ModuleId, QuotaWindow>>,
+}
+
+struct QuotaWindow {
+    start: Instant,
+    used: u64,
+}
+
+impl SignatureQuotas {
+    pub fn new(quota: SignatureQuota) -> Self {
+        Self {
+            max_signatures: quota.max_signatures,
+            window: Duration::from_secs(quota.window_secs),
+            windows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Counts a new signature for the module, failing if the module already
+    /// used all its quota for the current window
+    pub fn consume(&self, module_id: &ModuleId) -> Result<(), SignerModuleError> {
+        self.consume_at(module_id, Instant::now())
+    }
+
+    fn consume_at(&self, module_id: &ModuleId, now: Instant) -> Result<(), SignerModuleError> {
+        let mut windows = self.windows.lock().expect("quota lock poisoned");
+        let window =
+            windows.entry(module_id.clone()).or_insert(QuotaWindow { start: now, used: 0 });
+        self.roll_over(window, now);
+
+        if window.used >= self.max_signatures {
+            return Err(SignerModuleError::QuotaExceeded);
+        }
+
+        window.used += 1;
+        SIGNER_QUOTA_USED.with_label_values(&[module_id.as_str()]).set(window.used as i64);
+
+        Ok(())
+    }
+
+    /// Resets the windows that ended, so the quota used by a module that
+    /// stopped signing isn't reported for the next windows
+    pub fn reset_ended_windows(&self) {
+        self.reset_ended_windows_at(Instant::now())
+    }
+
+    fn reset_ended_windows_at(&self, now: Instant) {
+        let mut windows = self.windows.lock().expect("quota lock poisoned");
+        for (module_id, window) in windows.iter_mut() {
+            if self.roll_over(window, now) {
+                SIGNER_QUOTA_USED.with_label_values(&[module_id.as_str()]).set(0);
+            }
+        }
+    }
+
+    /// Starts a new window if the current one ended, returns whether it did
+    fn roll_over(&self, window: &mut QuotaWindow, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(window.start);
+        if elapsed < self.window {
+            return false;
+        }
+
+        // align to the window boundary so the reset time doesn't drift with requests
+        let windows_passed = (elapsed.as_secs_f64() / self.window.as_secs_f64()).floor();
+        window.start += self.window.mul_f64(windows_passed);
+        window.used = 0;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quotas(max_signatures: u64) -> SignatureQuotas {
+        SignatureQuotas::new(SignatureQuota { max_signatures, window_secs: 60 })
+    }
+
+    #[test]
+    fn test_quota_exceeded() {
+        let quotas = quotas(2);
+        let module_id = ModuleId("TEST_MODULE".to_string());
+        let now = Instant::now();
+
+        assert!(quotas.consume_at(&module_id, now).is_ok());
+        assert!(quotas.consume_at(&module_id, now).is_ok());
+
+        let err = quotas.consume_at(&module_id, now).unwrap_err();
+        assert!(matches!(err, SignerModuleError::QuotaExceeded));
+    }
+
+    #[test]
+    fn test_quota_is_per_module() {
+        let quotas = quotas(1);
+        let now = Instant::now();
+
+        assert!(quotas.consume_at(&ModuleId("MODULE_A".to_string()), now).is_ok());
+        assert!(quotas.consume_at(&ModuleId("MODULE_B".to_string()), now).is_ok());
+        assert!(quotas.consume_at(&ModuleId("MODULE_A".to_string()), now).is_err());
+    }
+
+    #[test]
+    fn test_quota_resets_on_window_boundary() {
+        let quotas = quotas(1);
+        let module_id = ModuleId("TEST_MODULE".to_string());
+        let now = Instant::now();
+
+        assert!(quotas.consume_at(&module_id, now).is_ok());
+        assert!(quotas.consume_at(&module_id, now + Duration::from_secs(59)).is_err());
+        assert!(quotas.consume_at(&module_id, now + Duration::from_secs(60)).is_ok());
+        // the new window started at 60s, not at the time of the last request
+        assert!(quotas.consume_at(&module_id, now + Duration::from_secs(119)).is_err());
+        assert!(quotas.consume_at(&module_id, now + Duration::from_secs(150)).is_ok());
+    }
+
+    #[test]
+    fn test_quota_metric_resets_with_window() {
+        let quotas = quotas(5);
+        let module_id = ModuleId("TEST_QUOTA_METRIC".to_string());
+        let used = || SIGNER_QUOTA_USED.with_label_values(&[module_id.as_str()]).get();
+        let now = Instant::now();
+
+        assert!(quotas.consume_at(&module_id, now).is_ok());
+        assert!(quotas.consume_at(&module_id, now).is_ok());
+        quotas.reset_ended_windows_at(now + Duration::from_secs(59));
+        assert_eq!(used(), 2);
+
+        quotas.reset_ended_windows_at(now + Duration::from_secs(60));
+        assert_eq!(used(), 0);
+    }
+}
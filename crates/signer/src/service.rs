@@ -1,11 +1,24 @@
-use std::{net::SocketAddr, sync::Arc};
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+    future::IntoFuture,
+    net::SocketAddr,
+    os::unix::fs::{FileTypeExt, PermissionsExt},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use axum::{
-    extract::{Request, State},
-    http::StatusCode,
+    body::{Body, Bytes},
+    extract::{Query, Request, State},
+    http::{
+        header::{ACCEPT, CONTENT_TYPE},
+        HeaderMap, HeaderValue, Method, StatusCode, Uri,
+    },
     middleware::{self, Next},
     response::{IntoResponse, Response},
-    routing::{get, post},
+    routing::{delete, get, post},
     Extension, Json,
 };
 use axum_extra::TypedHeader;
@@ -13,28 +26,66 @@ use bimap::BiHashMap;
 use cb_common::{
     commit::{
         constants::{
-            GENERATE_PROXY_KEY_PATH, GET_PUBKEYS_PATH, REQUEST_SIGNATURE_PATH, STATUS_PATH,
+            CHECK_KEYS_PATH, DELETE_PROXY_KEYS_PATH, GENERATE_PROXY_KEY_PATH, GET_PUBKEYS_PATH,
+            NDJSON_CONTENT_TYPE, PERMISSIONS_PATH, PROOF_OF_POSSESSION_PATH, REQUEST_EXAMPLES_PATH,
+            REQUEST_ID_HEADER, REQUEST_NONCE_HEADER, REQUEST_SIGNATURE_PATH,
+            SIGNER_ALL_PUBKEYS_PATH, SIGNER_CONFIG_PATH, SIGNER_EXPORT_PROXIES_PATH,
+            SIGNER_IMPORT_PROXIES_PATH, SIGNER_KEY_USAGE_PATH, SIGNER_MODULE_DENYLIST_PATH,
+            SIGNER_REDELEGATE_PROXIES_PATH, SIGNER_REENCRYPT_KEYSTORES_PATH, SIGNER_RELOAD_PATH,
+            SIGNER_STATS_PATH, SIGN_VALIDATOR_REGISTRATION_PATH, SSZ_CONTENT_TYPE, STATUS_PATH,
         },
         request::{
-            EncryptionScheme, GenerateProxyRequest, GetPubkeysResponse, SignConsensusRequest,
-            SignProxyRequest, SignRequest,
+            CheckKeysRequest, CheckKeysResponse, ConsensusProxyMap, DeleteProxyKeyResult,
+            DeleteProxyKeyStatus, DeleteProxyKeysRequest, DeleteProxyKeysResponse,
+            EncryptionScheme, GenerateProxyRequest, GenerateProxyResponse, GetPubkeysResponse,
+            KeyKind, ModulePermissions, OrphanProxies, ProofOfPossessionRequest, ProxyPublicKey,
+            PubkeyEncoded, PubkeyEncoding, RequestExamples, SignConsensusRequest, SignProxyRequest,
+            SignRequest, SignatureWithRoot, ValidatorRegistrationMessage,
         },
     },
-    config::StartSignerConfig,
-    constants::{COMMIT_BOOST_COMMIT, COMMIT_BOOST_VERSION},
+    config::{
+        check_jwts_strength, load_jwts_from_file, OrphanProxyPolicy, PersistedStats,
+        ProxySelection, SignatureQuota, SignerBind, StartSignerConfig, StatusEndpoint,
+        StoreFailurePolicy, WithdrawalCheck,
+    },
+    constants::{COMMIT_BOOST_COMMIT, COMMIT_BOOST_DOMAIN, COMMIT_BOOST_VERSION},
+    signature::verify_signed_root,
+    signer::{BlsPublicKey, BlsSigner, ConsensusSigner, ProxyStore, ReencryptError, SignerLoader},
     types::{Chain, Jwt, ModuleId},
+    utils::ms_into_slot,
 };
 use cb_metrics::provider::MetricsProvider;
-use eyre::{Context, Result};
+use eyre::{ensure, Context, Result};
 use headers::{authorization::Bearer, Authorization};
-use tokio::{net::TcpListener, sync::RwLock};
-use tracing::{debug, error, info, warn};
+use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq;
+use tokio::{
+    net::{TcpListener, UnixListener},
+    sync::RwLock,
+    task::JoinSet,
+};
+use tracing::{debug, error, field, info, info_span, warn, Instrument, Span};
 use uuid::Uuid;
 
 use crate::{
+    bundle::ProxyBundle,
+    constants::{
+        JWTS_RELOAD_INTERVAL_SECS, MAX_REQUEST_ID_LEN, ORPHAN_PASSWORD_GRACE_SECS,
+        QUOTA_RESET_INTERVAL_SECS,
+    },
+    denylist::ModuleDenylist,
     error::SignerModuleError,
     manager::SigningManager,
-    metrics::{uri_to_tag, SIGNER_METRICS_REGISTRY, SIGNER_STATUS},
+    metrics::{
+        slot_phase, uri_to_tag, SIGNER_CANCELLED_REQUESTS, SIGNER_METRICS_REGISTRY,
+        SIGNER_ORPHAN_PROXIES, SIGNER_SIGN_LATENCY, SIGNER_STATUS, SIGNER_TIMED_OUT_REQUESTS,
+        SIGNER_UNAUTHORIZED, SIGNER_UNKNOWN_CONSENSUS_SIGNER, SIGNER_UNKNOWN_PROXY_SIGNER,
+    },
+    nonce::NonceTracker,
+    quota::SignatureQuotas,
+    stats::SignerStats,
+    usage::{KeyUsage, KeyUsageEntry, SignOperation},
+    withdrawal::{self, check_withdrawal_credentials},
 };
 
 /// Implements the Signer API and provides a service for signing requests
@@ -46,7 +97,147 @@ struct SigningState {
     manager: Arc<RwLock<SigningManager>>,
     /// Map of JWTs to module ids. This also acts as registry of all modules
     /// running
-    jwts: Arc<BiHashMap<ModuleId, Jwt>>,
+    jwts: Arc<RwLock<BiHashMap<ModuleId, Jwt>>>,
+    /// Modules refused access even with a valid JWT
+    denylist: Arc<RwLock<ModuleDenylist>>,
+    /// Signature quotas per module, if configured
+    quotas: Option<Arc<SignatureQuotas>>,
+    /// Max time to produce a signature, if configured
+    sign_timeout: Option<Duration>,
+    /// Chain the signer runs on, to find the phase of the slot of a request
+    chain: Chain,
+    /// Last signature of each key
+    usage: Arc<KeyUsage>,
+    /// Cumulative totals, persisted if configured
+    stats: Arc<SignerStats>,
+    /// Last sign request nonce of each module, if nonces are required
+    nonces: Option<Arc<NonceTracker>>,
+    /// JWT for the admin endpoints
+    admin_jwt: Option<Jwt>,
+    /// Configuration the service is running with
+    config: Arc<RwLock<EffectiveConfig>>,
+}
+
+/// Configuration the signer was started with, without any secret
+#[derive(Serialize)]
+struct EffectiveConfig {
+    chain: Chain,
+    binds: Vec<SignerBind>,
+    loader: SignerLoader,
+    store: Option<ProxyStore>,
+    store_failure: StoreFailurePolicy,
+    consensus_allowlist: Option<Vec<BlsPublicKey>>,
+    signature_quota: Option<SignatureQuota>,
+    #[serde(serialize_with = "withdrawal::serialize_redacted")]
+    withdrawal_check: Option<WithdrawalCheck>,
+    max_consensus_keys: Option<usize>,
+    allow_empty_signer: bool,
+    verify_signatures: bool,
+    sign_timeout_ms: Option<u64>,
+    module_denylist: Option<Vec<ModuleId>>,
+    registration_modules: Option<Vec<ModuleId>>,
+    pop_modules: Option<Vec<ModuleId>>,
+    status_endpoint: StatusEndpoint,
+    prune_passwords_interval_secs: Option<u64>,
+    max_proxies_per_key: Option<usize>,
+    proxy_selection: ProxySelection,
+    orphan_proxies: OrphanProxyPolicy,
+    min_jwt_length: usize,
+    strict_jwts: bool,
+    jwts_file: Option<PathBuf>,
+    persisted_stats: Option<PersistedStats>,
+    nonce_file: Option<PathBuf>,
+    nonce_window: usize,
+}
+
+#[derive(Deserialize)]
+struct UpdateDenylistRequest {
+    module_id: ModuleId,
+    denied: bool,
+}
+
+#[derive(Deserialize)]
+struct SignatureQuery {
+    /// Return the signing root together with the signature, JSON only
+    #[serde(default)]
+    include_signing_root: bool,
+    /// Slot the signature is for, only used for the latency metric
+    slot: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct PubkeysQuery {
+    /// Hex encoding of the returned pubkeys, `0x`-prefixed by default
+    #[serde(default)]
+    pubkey_encoding: PubkeyEncoding,
+}
+
+#[derive(Deserialize)]
+struct RedelegateProxiesRequest {
+    old_consensus: BlsPublicKey,
+    new_consensus: BlsPublicKey,
+}
+
+/// Id of a request used in the logs, sent by the module in the `X-Request-Id`
+/// header or generated
+#[derive(Clone)]
+struct RequestId(String);
+
+impl Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[derive(Serialize)]
+struct DenylistResponse {
+    denied_modules: Vec<ModuleId>,
+}
+
+#[derive(Serialize)]
+struct KeyUsageResponse {
+    keys: Vec<KeyUsageEntry>,
+}
+
+#[derive(Deserialize)]
+struct ExportProxiesRequest {
+    passphrase: String,
+}
+
+#[derive(Deserialize)]
+struct ImportProxiesRequest {
+    passphrase: String,
+    bundle: ProxyBundle,
+}
+
+#[derive(Deserialize)]
+struct ReencryptKeystoresRequest {
+    old_password: String,
+    new_password: String,
+}
+
+/// Last line of the NDJSON pubkeys response if orphans are reported, tagged so
+/// it can't be mistaken for a key
+#[derive(Serialize)]
+struct NdjsonOrphans<'a> {
+    orphans: PubkeyEncoded<'a, OrphanProxies>,
+}
+
+#[derive(Serialize)]
+struct GetAllPubkeysResponse<'a> {
+    modules: PubkeyEncoded<'a, HashMap<ModuleId, Vec<ConsensusProxyMap>>>,
+}
+
+#[derive(Serialize)]
+struct SignerConfigResponse<'a> {
+    signer_type: &'static str,
+    #[serde(flatten)]
+    config: &'a EffectiveConfig,
+    modules: Vec<ModuleId>,
+    denied_modules: Vec<ModuleId>,
+    loaded_consensus: usize,
+    loaded_proxies_bls: usize,
+    loaded_proxies_ecdsa: usize,
 }
 
 impl SigningService {
@@ -56,44 +247,144 @@ impl SigningService {
             return Ok(());
         }
 
-        let proxy_store = if let Some(store) = config.store {
-            Some(store.init_from_env()?)
-        } else {
-            warn!("Proxy store not configured. Proxies keys and delegations will not be persisted");
-            None
+        let mut manager = init_signing_manager(config.chain, config.store, config.store_failure)?;
+        manager.set_verify_signatures(config.verify_signatures);
+        let proxy_store = manager.proxy_store().cloned();
+
+        let effective_config = EffectiveConfig {
+            chain: config.chain,
+            binds: config.binds.clone(),
+            loader: config.loader.clone(),
+            store: proxy_store.clone(),
+            store_failure: config.store_failure,
+            consensus_allowlist: config.consensus_allowlist.clone(),
+            signature_quota: config.signature_quota,
+            withdrawal_check: config.withdrawal_check.clone(),
+            max_consensus_keys: config.max_consensus_keys,
+            allow_empty_signer: config.allow_empty_signer,
+            verify_signatures: config.verify_signatures,
+            sign_timeout_ms: config.sign_timeout_ms,
+            module_denylist: config.module_denylist.clone(),
+            registration_modules: config.registration_modules.clone(),
+            pop_modules: config.pop_modules.clone(),
+            status_endpoint: config.status_endpoint,
+            prune_passwords_interval_secs: config.prune_passwords_interval_secs,
+            max_proxies_per_key: config.max_proxies_per_key,
+            proxy_selection: config.proxy_selection,
+            orphan_proxies: config.orphan_proxies,
+            min_jwt_length: config.min_jwt_length,
+            strict_jwts: config.strict_jwts,
+            jwts_file: config.jwts_file.clone(),
+            persisted_stats: config.persisted_stats.clone(),
+            nonce_file: config.nonce_file.clone(),
+            nonce_window: config.nonce_window,
         };
 
-        let mut manager = SigningManager::new(config.chain, proxy_store)?;
+        if let (Some(interval), Some(store)) =
+            (config.prune_passwords_interval_secs, proxy_store.clone())
+        {
+            tokio::spawn(prune_orphan_passwords(store, Duration::from_secs(interval)));
+        }
 
-        for signer in config.loader.load_keys()? {
-            manager.add_consensus_signer(signer);
+        let stats = match &config.persisted_stats {
+            Some(persisted) => SignerStats::load(&persisted.path)?,
+            None => SignerStats::default(),
+        };
+        let nonces = config
+            .nonce_file
+            .map(|path| NonceTracker::load(path, config.nonce_window))
+            .transpose()?;
+
+        let signers = load_consensus_keys(
+            config.loader,
+            config.max_consensus_keys,
+            config.allow_empty_signer,
+        )
+        .wrap_err("failed to load consensus keys")?;
+        if let Some(check) = &config.withdrawal_check {
+            let pubkeys: Vec<BlsPublicKey> = signers.iter().map(|signer| signer.pubkey()).collect();
+            check_withdrawal_credentials(check, &pubkeys).await?;
         }
+        let unlocked_keys = signers.len();
+        set_consensus_signers(&mut manager, signers, config.consensus_allowlist);
+
         let module_ids: Vec<String> = config.jwts.left_values().cloned().map(Into::into).collect();
 
-        let loaded_consensus = manager.consensus_pubkeys().len();
+        let loaded_consensus = manager.allowed_consensus_count();
         let proxies = manager.proxies();
         let loaded_proxies = proxies.bls_signers.len() + proxies.ecdsa_signers.len();
 
-        info!(version = COMMIT_BOOST_VERSION, commit = COMMIT_BOOST_COMMIT, modules =? module_ids, port =? config.server_port, loaded_consensus, loaded_proxies, "Starting signing service");
+        if loaded_consensus == 0 {
+            ensure!(
+                config.allow_empty_signer,
+                "no consensus key is loaded, the signer could never sign. Check the loader and the allowlist, or set allow_empty_signer to start anyway"
+            );
+            warn!("No consensus key is loaded, the signer can't sign until keys are reloaded");
+        }
 
-        let state = SigningState { manager: RwLock::new(manager).into(), jwts: config.jwts.into() };
+        info!(version = COMMIT_BOOST_VERSION, commit = COMMIT_BOOST_COMMIT, modules =? module_ids, binds =? config.binds, loaded_consensus, loaded_proxies, "Starting signing service");
+
+        let state = SigningState {
+            manager: RwLock::new(manager).into(),
+            jwts: Arc::new(RwLock::new(config.jwts)),
+            denylist: Arc::new(RwLock::new(ModuleDenylist::new(
+                config.module_denylist.into_iter().flatten(),
+            ))),
+            quotas: config.signature_quota.map(|quota| SignatureQuotas::new(quota).into()),
+            sign_timeout: config.sign_timeout_ms.map(Duration::from_millis),
+            chain: config.chain,
+            usage: Arc::new(KeyUsage::default()),
+            stats: Arc::new(stats),
+            nonces: nonces.map(Arc::new),
+            admin_jwt: config.admin_jwt,
+            config: Arc::new(RwLock::new(effective_config)),
+        };
         SigningService::init_metrics(config.chain)?;
+        warm_up_bls(config.chain).await?;
 
-        let app = axum::Router::new()
-            .route(REQUEST_SIGNATURE_PATH, post(handle_request_signature))
-            .route(GET_PUBKEYS_PATH, get(handle_get_pubkeys))
-            .route(GENERATE_PROXY_KEY_PATH, post(handle_generate_proxy))
-            .with_state(state.clone())
-            .route_layer(middleware::from_fn_with_state(state.clone(), jwt_auth))
-            .route_layer(middleware::from_fn(log_request));
-        let status_router = axum::Router::new().route(STATUS_PATH, get(handle_status));
+        state.stats.record_key_unlocks(unlocked_keys);
+        if let Some(quotas) = &state.quotas {
+            tokio::spawn(reset_quota_windows(quotas.clone()));
+        }
+        if let Some(persisted) = config.persisted_stats {
+            tokio::spawn(persist_stats(
+                state.stats.clone(),
+                persisted.path,
+                Duration::from_secs(persisted.interval_secs),
+            ));
+        }
+
+        if let Some(path) = config.jwts_file {
+            tokio::spawn(watch_jwts_file(path, state.jwts.clone(), state.config.clone()));
+        }
 
-        let address = SocketAddr::from(([0, 0, 0, 0], config.server_port));
-        let listener = TcpListener::bind(address).await?;
+        let router = build_router(state, config.status_endpoint);
 
-        axum::serve(listener, axum::Router::new().merge(app).merge(status_router))
-            .await
-            .wrap_err("signer server exited")
+        let mut servers = JoinSet::new();
+        for bind in config.binds {
+            match bind {
+                SignerBind::Tcp(port) => {
+                    let listener =
+                        TcpListener::bind(SocketAddr::from(([0, 0, 0, 0], port))).await?;
+                    servers.spawn(axum::serve(listener, router.clone()).into_future());
+                }
+                SignerBind::Unix { path, mode } => {
+                    let listener = bind_unix_socket(&path, mode)?;
+                    info!(
+                        ?path,
+                        mode = format!("{mode:o}"),
+                        "Serving the signer API on a unix socket"
+                    );
+                    servers.spawn(axum::serve(listener, router.clone()).into_future());
+                }
+            }
+        }
+
+        // stop as soon as any server exits, the signer shouldn't be partially reachable
+        match servers.join_next().await {
+            Some(result) => result?.wrap_err("signer server exited"),
+            None => eyre::bail!("signer has nothing to serve on"),
+        }
     }
 
     fn init_metrics(network: Chain) -> Result<()> {
@@ -101,6 +392,263 @@ impl SigningService {
     }
 }
 
+/// Routes of the module API, the status endpoint as configured, and the admin
+/// endpoints if an admin JWT is set. Unknown paths get a JSON error
+fn build_router(state: SigningState, status_endpoint: StatusEndpoint) -> axum::Router {
+    let app = axum::Router::new()
+        .route(REQUEST_SIGNATURE_PATH, post(handle_request_signature))
+        .route(GET_PUBKEYS_PATH, get(handle_get_pubkeys))
+        .route(GENERATE_PROXY_KEY_PATH, post(handle_generate_proxy))
+        .route(DELETE_PROXY_KEYS_PATH, delete(handle_delete_proxy_keys))
+        .route(CHECK_KEYS_PATH, post(handle_check_keys))
+        .route(REQUEST_EXAMPLES_PATH, get(handle_request_examples))
+        .route(SIGN_VALIDATOR_REGISTRATION_PATH, post(handle_sign_registration))
+        .route(PROOF_OF_POSSESSION_PATH, post(handle_proof_of_possession))
+        .route(PERMISSIONS_PATH, get(handle_get_permissions))
+        .with_state(state.clone())
+        .route_layer(middleware::from_fn_with_state(state.clone(), jwt_auth))
+        .route_layer(middleware::from_fn(log_request))
+        .route_layer(middleware::from_fn(request_id));
+    let mut router = axum::Router::new().merge(app);
+
+    let status_router = axum::Router::new().route(STATUS_PATH, get(handle_status));
+    match status_endpoint {
+        StatusEndpoint::Public => router = router.merge(status_router),
+        StatusEndpoint::Authenticated => {
+            router = router.merge(
+                status_router.route_layer(middleware::from_fn_with_state(state.clone(), jwt_auth)),
+            )
+        }
+        StatusEndpoint::Disabled => info!("Status endpoint is disabled"),
+    }
+
+    if state.admin_jwt.is_some() {
+        let admin_app = axum::Router::new()
+            .route(SIGNER_CONFIG_PATH, get(handle_get_config))
+            .route(SIGNER_RELOAD_PATH, post(handle_reload))
+            .route(SIGNER_ALL_PUBKEYS_PATH, get(handle_get_all_pubkeys))
+            .route(SIGNER_EXPORT_PROXIES_PATH, post(handle_export_proxies))
+            .route(SIGNER_IMPORT_PROXIES_PATH, post(handle_import_proxies))
+            .route(SIGNER_REDELEGATE_PROXIES_PATH, post(handle_redelegate_proxies))
+            .route(SIGNER_MODULE_DENYLIST_PATH, post(handle_update_denylist))
+            .route(SIGNER_KEY_USAGE_PATH, get(handle_get_key_usage))
+            .route(SIGNER_STATS_PATH, get(handle_get_stats))
+            .route(SIGNER_REENCRYPT_KEYSTORES_PATH, post(handle_reencrypt_keystores))
+            .with_state(state.clone())
+            .route_layer(middleware::from_fn_with_state(state.clone(), admin_auth))
+            .route_layer(middleware::from_fn(log_request))
+            .route_layer(middleware::from_fn(request_id));
+        router = router.merge(admin_app);
+    } else {
+        info!("Admin JWT not set, admin endpoints are disabled");
+    }
+
+    router.fallback(handle_unknown_route)
+}
+
+/// Binds the unix socket, replacing a stale one, with the given permissions
+fn bind_unix_socket(path: &Path, mode: u32) -> eyre::Result<UnixListener> {
+    remove_stale_socket(path)?;
+    let listener = UnixListener::bind(path)
+        .wrap_err_with(|| format!("failed to bind unix socket {path:?}"))?;
+    // access to the API is controlled by the socket permissions, don't rely on
+    // the umask
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+        .wrap_err_with(|| format!("failed to set permissions of {path:?}"))?;
+    Ok(listener)
+}
+
+/// Removes a socket left by a previous run, which would make the bind fail.
+/// Anything else at the path is never removed
+fn remove_stale_socket(path: &Path) -> eyre::Result<()> {
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => std::fs::remove_file(path)
+            .wrap_err_with(|| format!("failed to remove stale socket {path:?}")),
+        Ok(_) => eyre::bail!("{path:?} already exists and is not a socket"),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(err).wrap_err_with(|| format!("failed to check {path:?}")),
+    }
+}
+
+/// Initializes the proxy store and loads its proxies. If a configured store
+/// fails, the signer either doesn't start or continues without a store,
+/// depending on the policy
+fn init_signing_manager(
+    chain: Chain,
+    store: Option<ProxyStore>,
+    policy: StoreFailurePolicy,
+) -> eyre::Result<SigningManager> {
+    let Some(store) = store else {
+        warn!("Proxy store not configured. Proxies keys and delegations will not be persisted");
+        return SigningManager::new(chain, None);
+    };
+
+    let manager =
+        store.init_from_env().wrap_err("failed to initialize the proxy store").and_then(|store| {
+            SigningManager::new(chain, Some(store))
+                .wrap_err("failed to load proxies from the proxy store")
+        });
+
+    match (manager, policy) {
+        (Ok(manager), _) => Ok(manager),
+        (Err(err), StoreFailurePolicy::Fail) => {
+            Err(err
+                .wrap_err("proxy store failed, set store_failure to continue to start without it"))
+        }
+        (Err(err), StoreFailurePolicy::Continue) => {
+            error!(err = format!("{err:#}"), "Proxy store failed, continuing without it. Proxies keys and delegations will not be persisted");
+            SigningManager::new(chain, None)
+        }
+    }
+}
+
+/// Signs and verifies with a throwaway key, so the first real request doesn't
+/// pay for the initialization of the BLS library
+async fn warm_up_bls(chain: Chain) -> eyre::Result<()> {
+    let start = Instant::now();
+
+    let signer = BlsSigner::new_random();
+    let object_root = [0; 32];
+    let signature = signer.sign(chain, object_root).await;
+    verify_signed_root(chain, &signer.pubkey(), object_root, &signature, COMMIT_BOOST_DOMAIN)
+        .map_err(|err| eyre::eyre!("BLS warmup signature is invalid: {err}"))?;
+
+    info!(elapsed_ms = start.elapsed().as_millis() as u64, "Warmed up the BLS library");
+
+    Ok(())
+}
+
+/// Loads the consensus keys, failing if the loader finds more than the
+/// configured max, or none unless the signer is allowed to be empty
+fn load_consensus_keys(
+    loader: SignerLoader,
+    max_consensus_keys: Option<usize>,
+    allow_empty: bool,
+) -> eyre::Result<Vec<ConsensusSigner>> {
+    let signers = loader.load_keys()?;
+
+    ensure!(
+        allow_empty || !signers.is_empty(),
+        "found no consensus keys. Is the loader pointing to the right keys?"
+    );
+
+    if let Some(max) = max_consensus_keys {
+        ensure!(
+            signers.len() <= max,
+            "found {} consensus keys, more than the max of {max}. Is the loader pointing to the right keys?",
+            signers.len()
+        );
+    }
+
+    info!(count = signers.len(), "Loaded consensus keys");
+
+    Ok(signers)
+}
+
+/// Replaces the consensus signers in the manager and applies the allowlist
+fn set_consensus_signers(
+    manager: &mut SigningManager,
+    signers: Vec<ConsensusSigner>,
+    consensus_allowlist: Option<Vec<BlsPublicKey>>,
+) {
+    manager.replace_consensus_signers(signers);
+
+    if let Some(allowlist) = consensus_allowlist {
+        for pubkey in manager.consensus_pubkeys() {
+            if !allowlist.contains(&pubkey) {
+                warn!(%pubkey, "Consensus key loaded but not in allowlist, it will not be used to sign");
+            }
+        }
+        manager.set_consensus_allowlist(allowlist);
+    } else {
+        manager.clear_consensus_allowlist();
+    }
+}
+
+/// Polls the JWTs file and swaps the JWTs map when the file changes. If the new
+/// file is invalid, the previous JWTs are kept
+async fn watch_jwts_file(
+    path: PathBuf,
+    jwts: Arc<RwLock<BiHashMap<ModuleId, Jwt>>>,
+    config: Arc<RwLock<EffectiveConfig>>,
+) {
+    let modified_at = |path: &Path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+
+    let mut last_modified = modified_at(&path);
+    let mut interval = tokio::time::interval(Duration::from_secs(JWTS_RELOAD_INTERVAL_SECS));
+
+    loop {
+        interval.tick().await;
+
+        let modified = modified_at(&path);
+        if modified == last_modified {
+            continue;
+        }
+        last_modified = modified;
+
+        let (min_jwt_length, strict_jwts) = {
+            let config = config.read().await;
+            (config.min_jwt_length, config.strict_jwts)
+        };
+        let new_jwts = load_jwts_from_file(&path).and_then(|new_jwts| {
+            check_jwts_strength(&new_jwts, min_jwt_length, strict_jwts).map(|_| new_jwts)
+        });
+
+        match new_jwts {
+            Ok(new_jwts) => {
+                let module_ids: Vec<String> =
+                    new_jwts.left_values().cloned().map(Into::into).collect();
+                info!(modules =? module_ids, "Reloaded JWTs from file");
+                *jwts.write().await = new_jwts;
+            }
+            Err(err) => error!(%err, "Failed to reload JWTs file, keeping previous JWTs"),
+        }
+    }
+}
+
+/// Periodically removes the proxy passwords left without a keystore
+async fn prune_orphan_passwords(store: ProxyStore, period: Duration) {
+    let grace_period = Duration::from_secs(ORPHAN_PASSWORD_GRACE_SECS);
+    let mut interval = tokio::time::interval(period);
+
+    loop {
+        interval.tick().await;
+
+        match store.prune_orphan_passwords(grace_period) {
+            Ok(pruned) => {
+                for path in pruned {
+                    info!(?path, "Pruned orphan proxy password");
+                }
+            }
+            Err(err) => error!(%err, "Failed to prune orphan proxy passwords"),
+        }
+    }
+}
+
+/// Periodically saves the signer stats, failures are retried on the next tick
+async fn persist_stats(stats: Arc<SignerStats>, path: PathBuf, period: Duration) {
+    let mut interval = tokio::time::interval(period);
+
+    loop {
+        interval.tick().await;
+
+        if let Err(err) = stats.persist(&path) {
+            error!(%err, ?path, "Failed to persist signer stats");
+        }
+    }
+}
+
+/// Periodically resets the quota windows that ended, so the quota metric is
+/// accurate for modules that stopped signing
+async fn reset_quota_windows(quotas: Arc<SignatureQuotas>) {
+    let mut interval = tokio::time::interval(Duration::from_secs(QUOTA_RESET_INTERVAL_SECS));
+
+    loop {
+        interval.tick().await;
+        quotas.reset_ended_windows();
+    }
+}
+
 /// Authentication middleware layer
 async fn jwt_auth(
     State(state): State<SigningState>,
@@ -110,12 +658,41 @@ async fn jwt_auth(
 ) -> Result<Response, SignerModuleError> {
     let jwt: Jwt = auth.token().to_string().into();
 
-    let module_id = state.jwts.get_by_right(&jwt).ok_or_else(|| {
+    let module_id = state.jwts.read().await.get_by_right(&jwt).cloned().ok_or_else(|| {
         error!("Unauthorized request. Was the module started correctly?");
+        SIGNER_UNAUTHORIZED.with_label_values(&[uri_to_tag(req.uri())]).inc();
         SignerModuleError::Unauthorized
     })?;
 
-    req.extensions_mut().insert(module_id.clone());
+    if state.denylist.read().await.is_denied(&module_id) {
+        warn!(?module_id, "Request from denied module");
+        SIGNER_UNAUTHORIZED.with_label_values(&[uri_to_tag(req.uri())]).inc();
+        return Err(SignerModuleError::Unauthorized);
+    }
+
+    Span::current().record("module_id", module_id.as_str());
+    req.extensions_mut().insert(module_id);
+
+    Ok(next.run(req).await)
+}
+
+/// Authentication middleware layer for the admin endpoints
+async fn admin_auth(
+    State(state): State<SigningState>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    req: Request,
+    next: Next,
+) -> Result<Response, SignerModuleError> {
+    // constant-time compare, so the JWT can't be guessed from response times
+    let authorized = state
+        .admin_jwt
+        .as_ref()
+        .is_some_and(|jwt| bool::from(jwt.as_str().as_bytes().ct_eq(auth.token().as_bytes())));
+    if !authorized {
+        error!("Unauthorized admin request");
+        SIGNER_UNAUTHORIZED.with_label_values(&[uri_to_tag(req.uri())]).inc();
+        return Err(SignerModuleError::Unauthorized);
+    }
 
     Ok(next.run(req).await)
 }
@@ -125,98 +702,1341 @@ async fn log_request(req: Request, next: Next) -> Result<Response, SignerModuleE
     let url = &req.uri().clone();
     let response = next.run(req).await;
     SIGNER_STATUS.with_label_values(&[response.status().as_str(), uri_to_tag(url)]).inc();
+
+    Span::current().record("status", response.status().as_u16());
+    debug!(path = url.path(), "Request completed");
+
     Ok(response)
 }
 
+/// Takes the request id from the `X-Request-Id` header, generating one if it's
+/// missing or invalid, and echoes it in the response. The request runs in a
+/// span with the request id, module and status, so every log of the request
+/// can be correlated
+async fn request_id(mut req: Request, next: Next) -> Response {
+    let req_id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty() && value.len() <= MAX_REQUEST_ID_LEN)
+        .map(String::from)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    let span = info_span!("request", %req_id, module_id = field::Empty, status = field::Empty);
+    req.extensions_mut().insert(RequestId(req_id.clone()));
+    let mut response = next.run(req).instrument(span).await;
+
+    if let Ok(value) = HeaderValue::from_str(&req_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+
+    response
+}
+
+/// Fallback for unknown paths, these usually come from a misconfigured module
+/// or from probes
+async fn handle_unknown_route(method: Method, uri: Uri) -> SignerModuleError {
+    warn!(%method, path = uri.path(), "Request to unknown route");
+    SignerModuleError::UnknownRoute(uri.path().to_string())
+}
+
 /// Status endpoint for the Signer API
 async fn handle_status() -> Result<impl IntoResponse, SignerModuleError> {
     Ok((StatusCode::OK, "OK"))
 }
 
+/// Returns the configuration the signer is running with, secrets are never
+/// included
+async fn handle_get_config(
+    Extension(req_id): Extension<RequestId>,
+    State(state): State<SigningState>,
+) -> Result<impl IntoResponse, SignerModuleError> {
+    debug!(event = "get_config", %req_id, "New request");
+
+    let modules = state.jwts.read().await.left_values().cloned().collect();
+    let denied_modules = state.denylist.read().await.denied_modules();
+
+    let config = state.config.read().await;
+    let signing_manager = state.manager.read().await;
+    let proxies = signing_manager.proxies();
+
+    let res = SignerConfigResponse {
+        signer_type: "local",
+        config: &config,
+        modules,
+        denied_modules,
+        loaded_consensus: signing_manager.allowed_consensus_count(),
+        loaded_proxies_bls: proxies.bls_signers.len(),
+        loaded_proxies_ecdsa: proxies.ecdsa_signers.len(),
+    };
+
+    Ok((StatusCode::OK, Json(res)).into_response())
+}
+
+/// Reloads the consensus keys, JWTs and allowlist from the config. Changes to
+/// the port, store, quota, sign timeout, password pruning, status endpoint or
+/// JWTs file need a restart and are only warned about. The modules denied or
+/// allowed back at runtime stay so, on top of the denylist in the config
+async fn handle_reload(
+    Extension(req_id): Extension<RequestId>,
+    State(state): State<SigningState>,
+) -> Result<impl IntoResponse, SignerModuleError> {
+    debug!(event = "reload", %req_id, "New request");
+
+    let config = StartSignerConfig::load_from_env().map_err(|err| {
+        error!(%err, "Failed to load signer config");
+        SignerModuleError::Internal("failed to load signer config".into())
+    })?;
+
+    let store = config.store.map(ProxyStore::init_from_env).transpose().map_err(|err| {
+        error!(%err, "Failed to load proxy store config");
+        SignerModuleError::Internal("failed to load proxy store config".into())
+    })?;
+
+    {
+        let current = state.config.read().await;
+        if config.binds != current.binds {
+            warn!(
+                old =? current.binds,
+                new =? config.binds,
+                "Signer binds changed, restart the signer to apply them"
+            );
+        }
+        if store != current.store {
+            warn!("Proxy store changed, restart the signer to apply it");
+        }
+        if config.store_failure != current.store_failure {
+            warn!("Store failure policy changed, it only applies when the signer starts");
+        }
+        if config.signature_quota != current.signature_quota {
+            warn!("Signature quota changed, restart the signer to apply it");
+        }
+        if config.sign_timeout_ms != current.sign_timeout_ms {
+            warn!("Sign timeout changed, restart the signer to apply it");
+        }
+        if config.prune_passwords_interval_secs != current.prune_passwords_interval_secs {
+            warn!("Password prune interval changed, restart the signer to apply it");
+        }
+        if config.status_endpoint != current.status_endpoint {
+            warn!("Status endpoint changed, restart the signer to apply it");
+        }
+        if config.persisted_stats != current.persisted_stats {
+            warn!("Persisted stats changed, restart the signer to apply them");
+        }
+        if config.nonce_file != current.nonce_file || config.nonce_window != current.nonce_window {
+            warn!("Nonce settings changed, restart the signer to apply them");
+        }
+        if config.jwts_file != current.jwts_file {
+            warn!(
+                old =? current.jwts_file,
+                new =? config.jwts_file,
+                "JWTs file changed, restart the signer to watch the new one"
+            );
+        }
+    }
+
+    // load the keys before locking the manager, decrypting keystores can be slow
+    let signers = load_consensus_keys(
+        config.loader.clone(),
+        config.max_consensus_keys,
+        config.allow_empty_signer,
+    )
+    .map_err(|err| {
+        error!(%err, "Failed to reload consensus keys");
+        SignerModuleError::Internal("failed to reload consensus keys".into())
+    })?;
+    state.stats.record_key_unlocks(signers.len());
+
+    if let Some(check) = &config.withdrawal_check {
+        let pubkeys: Vec<BlsPublicKey> = signers.iter().map(|signer| signer.pubkey()).collect();
+        check_withdrawal_credentials(check, &pubkeys).await.map_err(|err| {
+            error!(%err, "Failed to check withdrawal credentials");
+            SignerModuleError::Internal("failed to check withdrawal credentials".into())
+        })?;
+    }
+
+    // like at startup, a signer left without any key that can sign is refused
+    let allowed_consensus = signers
+        .iter()
+        .filter(|signer| {
+            let allowlist = config.consensus_allowlist.as_ref();
+            allowlist.is_none_or(|allowlist| allowlist.contains(&signer.pubkey()))
+        })
+        .count();
+    if allowed_consensus == 0 && !config.allow_empty_signer {
+        error!("No consensus key would be loaded after the reload, the signer keeps the previous keys. Check the loader and the allowlist, or set allow_empty_signer to reload anyway");
+        return Err(SignerModuleError::Internal("no consensus key would be loaded".into()));
+    }
+
+    let module_ids: Vec<String> = config.jwts.left_values().cloned().map(Into::into).collect();
+    *state.jwts.write().await = config.jwts;
+    state.denylist.write().await.set_configured(config.module_denylist.iter().flatten().cloned());
+
+    // the keys and the config are swapped together, so no request sees the new
+    // keys with the old allowlist or module lists. The config is locked first,
+    // like everywhere both are taken
+    let mut current = state.config.write().await;
+    let mut signing_manager = state.manager.write().await;
+    set_consensus_signers(&mut signing_manager, signers, config.consensus_allowlist.clone());
+    signing_manager.set_verify_signatures(config.verify_signatures);
+
+    current.loader = config.loader;
+    current.consensus_allowlist = config.consensus_allowlist;
+    current.withdrawal_check = config.withdrawal_check;
+    current.max_consensus_keys = config.max_consensus_keys;
+    current.verify_signatures = config.verify_signatures;
+    current.module_denylist = config.module_denylist;
+    current.registration_modules = config.registration_modules;
+    current.pop_modules = config.pop_modules;
+    current.max_proxies_per_key = config.max_proxies_per_key;
+    current.proxy_selection = config.proxy_selection;
+    current.orphan_proxies = config.orphan_proxies;
+    current.min_jwt_length = config.min_jwt_length;
+    current.strict_jwts = config.strict_jwts;
+    current.allow_empty_signer = config.allow_empty_signer;
+
+    info!(modules =? module_ids, "Reloaded signer config");
+
+    Ok(StatusCode::OK)
+}
+
+/// Denies or allows back a module at runtime, until the next restart. The
+/// toggle takes precedence over the config, also after a reload
+async fn handle_update_denylist(
+    Extension(req_id): Extension<RequestId>,
+    State(state): State<SigningState>,
+    Json(request): Json<UpdateDenylistRequest>,
+) -> Result<impl IntoResponse, SignerModuleError> {
+    debug!(event = "update_denylist", %req_id, "New request");
+
+    let mut denylist = state.denylist.write().await;
+    denylist.toggle(request.module_id.clone(), request.denied);
+
+    info!(%req_id, module_id =? request.module_id, denied = request.denied, "Updated module denylist");
+
+    let res = DenylistResponse { denied_modules: denylist.denied_modules() };
+
+    Ok((StatusCode::OK, Json(res)).into_response())
+}
+
+/// Returns when each key last signed, to find proxies that are no longer used
+async fn handle_get_key_usage(
+    Extension(req_id): Extension<RequestId>,
+    State(state): State<SigningState>,
+) -> Result<impl IntoResponse, SignerModuleError> {
+    debug!(event = "get_key_usage", %req_id, "New request");
+
+    let res = KeyUsageResponse { keys: state.usage.entries() };
+
+    Ok((StatusCode::OK, Json(res)).into_response())
+}
+
+/// Returns the cumulative totals of the signer, kept across restarts if the
+/// stats are persisted
+async fn handle_get_stats(
+    Extension(req_id): Extension<RequestId>,
+    State(state): State<SigningState>,
+) -> Result<impl IntoResponse, SignerModuleError> {
+    debug!(event = "get_stats", %req_id, "New request");
+
+    Ok((StatusCode::OK, Json(state.stats.snapshot())).into_response())
+}
+
+/// Re-encrypts the keystores of the loaded consensus keys with a new password,
+/// and writes it to their password file
+async fn handle_reencrypt_keystores(
+    Extension(req_id): Extension<RequestId>,
+    State(state): State<SigningState>,
+    Json(request): Json<ReencryptKeystoresRequest>,
+) -> Result<impl IntoResponse, SignerModuleError> {
+    debug!(event = "reencrypt_keystores", %req_id, "New request");
+
+    if request.new_password.is_empty() {
+        return Err(SignerModuleError::InvalidRequest("the new password is empty".to_string()));
+    }
+
+    let loader = state.config.read().await.loader.clone();
+    let loaded = state.manager.read().await.consensus_pubkeys();
+    // every keystore goes through the KDF three times, keep it off the runtime
+    // workers
+    let reencrypted = tokio::task::spawn_blocking(move || {
+        loader.reencrypt_keystores(
+            &loaded,
+            request.old_password.as_bytes(),
+            request.new_password.as_bytes(),
+        )
+    })
+    .await
+    .map_err(|err| SignerModuleError::Internal(err.to_string()))?
+    .map_err(|err| {
+        warn!(%req_id, %err, "Failed to re-encrypt keystores");
+        match err {
+            ReencryptError::Unsupported(_) |
+            ReencryptError::WrongPassword(_) |
+            ReencryptError::NotLoaded(_) => SignerModuleError::InvalidRequest(err.to_string()),
+            ReencryptError::Partial { .. } | ReencryptError::Other(_) => {
+                SignerModuleError::Internal(err.to_string())
+            }
+        }
+    })?;
+
+    info!(
+        %req_id,
+        count = reencrypted.keystores.len(),
+        password_file = ?reencrypted.password_file,
+        "Re-encrypted keystores"
+    );
+
+    Ok((StatusCode::OK, Json(reencrypted)).into_response())
+}
+
+/// Returns the pubkeys available to every module, so monitoring doesn't need
+/// the JWTs of all modules
+async fn handle_get_all_pubkeys(
+    Extension(req_id): Extension<RequestId>,
+    State(state): State<SigningState>,
+    Query(query): Query<PubkeysQuery>,
+) -> Result<impl IntoResponse, SignerModuleError> {
+    debug!(event = "get_all_pubkeys", %req_id, "New request");
+
+    let module_ids: Vec<ModuleId> = state.jwts.read().await.left_values().cloned().collect();
+
+    let signing_manager = state.manager.read().await;
+    let modules = signing_manager
+        .get_all_consensus_proxy_maps(&module_ids)
+        .map_err(|err| SignerModuleError::Internal(err.to_string()))?;
+
+    let res = GetAllPubkeysResponse { modules: query.pubkey_encoding.encode(&modules) };
+
+    Ok((StatusCode::OK, Json(res)).into_response())
+}
+
+/// Exports the proxies of every module encrypted with a passphrase, so they
+/// can be imported in another signer
+async fn handle_export_proxies(
+    Extension(req_id): Extension<RequestId>,
+    State(state): State<SigningState>,
+    Json(request): Json<ExportProxiesRequest>,
+) -> Result<impl IntoResponse, SignerModuleError> {
+    debug!(event = "export_proxies", %req_id, "New request");
+
+    if request.passphrase.is_empty() {
+        return Err(SignerModuleError::InvalidRequest("the passphrase is empty".to_string()));
+    }
+
+    let contents = state.manager.read().await.export_proxies();
+    let (proxies_bls, proxies_ecdsa) = (contents.proxies_bls.len(), contents.proxies_ecdsa.len());
+
+    // the KDF is slow on purpose, keep it off the runtime workers
+    let bundle = tokio::task::spawn_blocking(move || contents.seal(request.passphrase.as_bytes()))
+        .await
+        .map_err(|err| SignerModuleError::Internal(err.to_string()))?
+        .map_err(|err| {
+            error!(%req_id, %err, "Failed to seal proxy bundle");
+            SignerModuleError::Internal("failed to seal proxy bundle".to_string())
+        })?;
+
+    info!(%req_id, proxies_bls, proxies_ecdsa, "Exported proxies");
+
+    Ok((StatusCode::OK, Json(bundle)).into_response())
+}
+
+async fn handle_import_proxies(
+    Extension(req_id): Extension<RequestId>,
+    State(state): State<SigningState>,
+    Json(request): Json<ImportProxiesRequest>,
+) -> Result<impl IntoResponse, SignerModuleError> {
+    debug!(event = "import_proxies", %req_id, "New request");
+
+    let chain = state.chain;
+    let ImportProxiesRequest { passphrase, bundle } = request;
+    let contents = tokio::task::spawn_blocking(move || bundle.open(chain, passphrase.as_bytes()))
+        .await
+        .map_err(|err| SignerModuleError::Internal(err.to_string()))?
+        .map_err(|err| {
+            warn!(%req_id, %err, "Failed to open proxy bundle");
+            SignerModuleError::InvalidRequest(err.to_string())
+        })?;
+
+    let result = state.manager.write().await.import_proxies(contents).inspect_err(|err| {
+        warn!(%req_id, %err, "Failed to import proxies");
+    })?;
+
+    info!(%req_id, imported = result.imported, skipped = result.skipped, "Imported proxies");
+
+    Ok((StatusCode::OK, Json(result)).into_response())
+}
+
+/// Moves the proxies of a consensus key to another one, e.g. after a key
+/// rotation. Both keys must be loaded
+async fn handle_redelegate_proxies(
+    Extension(req_id): Extension<RequestId>,
+    State(state): State<SigningState>,
+    Json(request): Json<RedelegateProxiesRequest>,
+) -> Result<impl IntoResponse, SignerModuleError> {
+    debug!(event = "redelegate_proxies", %req_id, "New request");
+
+    let result = state
+        .manager
+        .write()
+        .await
+        .redelegate_proxies(&request.old_consensus, &request.new_consensus)
+        .await
+        .inspect_err(|err| {
+            warn!(%req_id, %err, "Failed to redelegate proxies");
+        })?;
+
+    info!(
+        %req_id,
+        old_consensus = %request.old_consensus,
+        new_consensus = %request.new_consensus,
+        proxies_bls = result.proxies_bls.len(),
+        proxies_ecdsa = result.proxies_ecdsa.len(),
+        "Redelegated proxies"
+    );
+
+    Ok((StatusCode::OK, Json(result)).into_response())
+}
+
 /// Implements get_pubkeys from the Signer API
 async fn handle_get_pubkeys(
+    Extension(req_id): Extension<RequestId>,
     Extension(module_id): Extension<ModuleId>,
     State(state): State<SigningState>,
+    Query(query): Query<PubkeysQuery>,
+    headers: HeaderMap,
 ) -> Result<impl IntoResponse, SignerModuleError> {
-    let req_id = Uuid::new_v4();
+    debug!(event = "get_pubkeys", %req_id, "New request");
 
-    debug!(event = "get_pubkeys", ?req_id, "New request");
+    // copied out so the config lock is never taken while holding the manager
+    // lock, reloads take them in the other order
+    let (orphan_policy, max_proxies_per_key, proxy_selection) = {
+        let config = state.config.read().await;
+        (config.orphan_proxies, config.max_proxies_per_key, config.proxy_selection)
+    };
 
     let signing_manager = state.manager.read().await;
-    let map = signing_manager
-        .get_consensus_proxy_maps(&module_id)
-        .map_err(|err| SignerModuleError::Internal(err.to_string()))?;
+    let (mut map, orphans) = match orphan_policy {
+        OrphanProxyPolicy::Fail => {
+            let map = signing_manager
+                .get_consensus_proxy_maps(&module_id)
+                .map_err(|err| SignerModuleError::Internal(err.to_string()))?;
+            (map, None)
+        }
+        OrphanProxyPolicy::Report => {
+            let (map, orphans) = signing_manager
+                .split_consensus_proxy_maps(&module_id)
+                .map_err(|err| SignerModuleError::Internal(err.to_string()))?;
+
+            SIGNER_ORPHAN_PROXIES
+                .with_label_values(&[module_id.as_str()])
+                .set(orphans.len() as i64);
+            if !orphans.is_empty() {
+                warn!(%req_id, %module_id, orphans = orphans.len(), "Module has proxies whose consensus key is not loaded");
+            }
+            (map, Some(orphans))
+        }
+    };
+
+    if let Some(max_proxies) = max_proxies_per_key {
+        for keys in map.iter_mut() {
+            let bls_capped =
+                select_proxies(&mut keys.proxy_bls, max_proxies, proxy_selection, &state.usage);
+            let ecdsa_capped =
+                select_proxies(&mut keys.proxy_ecdsa, max_proxies, proxy_selection, &state.usage);
+            keys.has_more_proxies = bls_capped || ecdsa_capped;
+            keys.retain_listed_created_at();
+        }
+    }
+
+    let is_ndjson = headers
+        .get(ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.contains(NDJSON_CONTENT_TYPE));
+
+    // the keys are already collected, but each line is only serialized as the
+    // body is sent, so the serialized response is never held in memory at once
+    if is_ndjson {
+        let encoding = query.pubkey_encoding;
+        let lines = map.into_iter().map(move |keys| ndjson_line(&encoding.encode(&keys))).chain(
+            orphans.map(move |orphans| {
+                ndjson_line(&NdjsonOrphans { orphans: encoding.encode(&orphans) })
+            }),
+        );
+        let body = Body::from_stream(futures::stream::iter(lines));
+        return Ok(([(CONTENT_TYPE, NDJSON_CONTENT_TYPE)], body).into_response());
+    }
+
+    let res = GetPubkeysResponse { keys: map, orphans };
 
-    let res = GetPubkeysResponse { keys: map };
+    Ok((StatusCode::OK, Json(query.pubkey_encoding.encode(&res))).into_response())
+}
+
+fn ndjson_line<T: Serialize>(value: &T) -> Result<Bytes, serde_json::Error> {
+    let mut line = serde_json::to_vec(value)?;
+    line.push(b'\n');
+    Ok(Bytes::from(line))
+}
+
+/// Keeps at most `max` proxies in the given order, returns whether any was
+/// left out
+fn select_proxies<T: Copy + Display + Into<ProxyPublicKey>>(
+    proxies: &mut Vec<T>,
+    max: usize,
+    selection: ProxySelection,
+    usage: &KeyUsage,
+) -> bool {
+    if proxies.len() <= max {
+        return false;
+    }
+
+    match selection {
+        ProxySelection::MostRecent => usage.sort_most_recent_first(proxies),
+        ProxySelection::Lexicographic => proxies.sort_by_cached_key(|proxy| proxy.to_string()),
+    }
+    proxies.truncate(max);
+
+    true
+}
+
+/// Checks which of the given keys the module can sign with, so missing keys
+/// are found before they are needed
+async fn handle_check_keys(
+    Extension(req_id): Extension<RequestId>,
+    Extension(module_id): Extension<ModuleId>,
+    State(state): State<SigningState>,
+    Json(request): Json<CheckKeysRequest>,
+) -> Result<impl IntoResponse, SignerModuleError> {
+    debug!(event = "check_keys", ?module_id, %req_id, "New request");
+
+    let signing_manager = state.manager.read().await;
+    let keys = request
+        .pubkeys
+        .into_iter()
+        .map(|pubkey| signing_manager.check_key(&module_id, pubkey))
+        .collect();
+
+    let res = CheckKeysResponse { keys };
 
     Ok((StatusCode::OK, Json(res)).into_response())
 }
 
-/// Implements request_signature from the Signer API
+/// Returns an example body for each request, to check the format when
+/// integrating a module
+async fn handle_request_examples() -> Result<impl IntoResponse, SignerModuleError> {
+    Ok((StatusCode::OK, Json(RequestExamples::default())).into_response())
+}
+
+/// Returns what the calling module is allowed to do. Only the caller's own
+/// permissions are returned
+async fn handle_get_permissions(
+    Extension(req_id): Extension<RequestId>,
+    Extension(module_id): Extension<ModuleId>,
+    State(state): State<SigningState>,
+) -> Result<impl IntoResponse, SignerModuleError> {
+    debug!(event = "get_permissions", ?module_id, %req_id, "New request");
+
+    let generate_proxy = state.manager.read().await.has_proxy_store();
+
+    let config = state.config.read().await;
+    let is_listed =
+        |modules: &Option<Vec<ModuleId>>| modules.as_ref().is_some_and(|m| m.contains(&module_id));
+    let res = ModulePermissions {
+        module_id: module_id.clone(),
+        generate_proxy,
+        sign_validator_registration: is_listed(&config.registration_modules),
+        proof_of_possession: is_listed(&config.pop_modules),
+        signature_quota: config.signature_quota,
+        max_proxies_per_key: config.max_proxies_per_key,
+        sign_timeout_ms: config.sign_timeout_ms,
+    };
+
+    Ok((StatusCode::OK, Json(res)).into_response())
+}
+
+/// Implements request_signature from the Signer API. Requests with the SSZ
+/// content type are answered with the SSZ encoded signature, otherwise JSON is
+/// used
 async fn handle_request_signature(
+    Extension(req_id): Extension<RequestId>,
     Extension(module_id): Extension<ModuleId>,
     State(state): State<SigningState>,
-    Json(request): Json<SignRequest>,
+    Query(query): Query<SignatureQuery>,
+    headers: HeaderMap,
+    body: Bytes,
 ) -> Result<impl IntoResponse, SignerModuleError> {
-    let req_id = Uuid::new_v4();
+    let is_ssz = headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with(SSZ_CONTENT_TYPE));
 
-    debug!(event = "request_signature", ?module_id, ?req_id, "New request");
+    let request = if is_ssz {
+        SignRequest::from_ssz_bytes(&body)
+            .map_err(|err| SignerModuleError::InvalidRequest(err.to_string()))?
+    } else {
+        Json::<SignRequest>::from_bytes(&body)
+            .map_err(|err| SignerModuleError::InvalidRequest(err.body_text()))?
+            .0
+    };
 
-    let signing_manager = state.manager.read().await;
+    debug!(event = "request_signature", ?module_id, %req_id, "New request");
 
-    let signature_response = match request {
-        SignRequest::Consensus(SignConsensusRequest { pubkey, object_root }) => signing_manager
-            .sign_consensus(&pubkey, &object_root)
-            .await
-            .map(|sig| Json(sig).into_response()),
-        SignRequest::ProxyBls(SignProxyRequest { pubkey: bls_pk, object_root }) => {
-            if !signing_manager.has_proxy_bls_for_module(&bls_pk, &module_id) {
-                return Err(SignerModuleError::UnknownProxySigner(bls_pk.to_vec()));
-            }
+    let latency_timer = query.slot.map(|slot| {
+        let phase = slot_phase(ms_into_slot(slot, state.chain), state.chain.slot_time_sec() * 1000);
+        SIGNER_SIGN_LATENCY.with_label_values(&[phase]).start_timer()
+    });
 
-            signing_manager
-                .sign_proxy_bls(&bls_pk, &object_root)
-                .await
-                .map(|sig| Json(sig).into_response())
+    if is_ssz && query.include_signing_root {
+        return Err(SignerModuleError::InvalidRequest(
+            "the signing root can only be returned in JSON responses".to_string(),
+        ));
+    }
+
+    check_nonce(&state, &module_id, &req_id, &headers).await?;
+
+    // if the module disconnects, the server drops this future and the signature
+    // is never computed
+    let in_flight = InFlightRequest::new(&module_id, &req_id);
+
+    let (pubkey, kind, operation, object_root): (ProxyPublicKey, _, _, _) = match &request {
+        SignRequest::Consensus(request) => (
+            request.pubkey.into(),
+            KeyKind::Consensus,
+            SignOperation::Consensus,
+            request.object_root,
+        ),
+        SignRequest::ProxyBls(request) => {
+            (request.pubkey.into(), KeyKind::ProxyBls, SignOperation::ProxyBls, request.object_root)
         }
-        SignRequest::ProxyEcdsa(SignProxyRequest { pubkey: ecdsa_pk, object_root }) => {
-            if !signing_manager.has_proxy_ecdsa_for_module(&ecdsa_pk, &module_id) {
-                return Err(SignerModuleError::UnknownProxySigner(ecdsa_pk.to_vec()));
-            }
+        SignRequest::ProxyEcdsa(request) => (
+            request.pubkey.into(),
+            KeyKind::ProxyEcdsa,
+            SignOperation::ProxyEcdsa,
+            request.object_root,
+        ),
+    };
 
-            signing_manager
-                .sign_proxy_ecdsa(&ecdsa_pk, &object_root)
+    let sign = async {
+        let signing_manager = state.manager.read().await;
+        if signing_manager.check_key(&module_id, pubkey).kind == Some(kind) {
+            consume_quota(&state, &module_id, &req_id)?;
+        }
+        let signing_root =
+            query.include_signing_root.then(|| signing_manager.signing_root(&object_root));
+
+        match request {
+            SignRequest::Consensus(SignConsensusRequest { pubkey, object_root }) => signing_manager
+                .sign_consensus(&pubkey, &object_root)
                 .await
-                .map(|sig| Json(sig).into_response())
+                .map(|sig| signature_response(sig, is_ssz, signing_root)),
+            SignRequest::ProxyBls(SignProxyRequest { pubkey: bls_pk, object_root }) => {
+                if signing_manager.has_proxy_bls_for_module(&bls_pk, &module_id) {
+                    signing_manager
+                        .sign_proxy_bls(&bls_pk, &object_root)
+                        .await
+                        .map(|sig| signature_response(sig, is_ssz, signing_root))
+                } else {
+                    Err(SignerModuleError::UnknownProxySigner(bls_pk.to_vec()))
+                }
+            }
+            SignRequest::ProxyEcdsa(SignProxyRequest { pubkey: ecdsa_pk, object_root }) => {
+                if signing_manager.has_proxy_ecdsa_for_module(&ecdsa_pk, &module_id) {
+                    signing_manager
+                        .sign_proxy_ecdsa(&ecdsa_pk, &object_root)
+                        .await
+                        .map(|sig| signature_response(sig, is_ssz, signing_root))
+                } else {
+                    Err(SignerModuleError::UnknownProxySigner(ecdsa_pk.to_vec()))
+                }
+            }
+        }
+    };
+
+    let signature_response = with_sign_timeout(&state, &module_id, &req_id, sign)
+        .await
+        .inspect(|_| {
+            state.usage.record(pubkey, operation);
+            state.stats.record_signature();
+        })
+        .inspect_err(|err| record_module_error(&module_id, err));
+
+    in_flight.complete();
+
+    if let Some(timer) = latency_timer {
+        timer.observe_duration();
+    }
+
+    signature_response
+}
+
+/// Checks the nonce of a sign request, before anything else so replayed
+/// requests don't use the quota up
+async fn check_nonce(
+    state: &SigningState,
+    module_id: &ModuleId,
+    req_id: &RequestId,
+    headers: &HeaderMap,
+) -> Result<(), SignerModuleError> {
+    if let Some(nonces) = &state.nonces {
+        let nonce = headers
+            .get(REQUEST_NONCE_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok());
+        nonces.check(module_id, nonce).await.inspect_err(|err| {
+            warn!(?module_id, %req_id, %err, "Rejected sign request nonce");
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Counts a sign request in the module quota. Only called once the key to sign
+/// with is found, so requests for unknown keys or that time out waiting for the
+/// signer don't use the quota up
+fn consume_quota(
+    state: &SigningState,
+    module_id: &ModuleId,
+    req_id: &RequestId,
+) -> Result<(), SignerModuleError> {
+    if let Some(quotas) = &state.quotas {
+        quotas.consume(module_id).inspect_err(|_| {
+            warn!(?module_id, %req_id, "Signature quota exceeded");
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Awaits the signature, failing if it takes longer than the sign timeout
+async fn with_sign_timeout<T>(
+    state: &SigningState,
+    module_id: &ModuleId,
+    req_id: &RequestId,
+    sign: impl std::future::Future<Output = Result<T, SignerModuleError>>,
+) -> Result<T, SignerModuleError> {
+    match state.sign_timeout {
+        Some(timeout) => tokio::time::timeout(timeout, sign).await.unwrap_or_else(|_| {
+            warn!(?module_id, %req_id, "Sign request timed out");
+            SIGNER_TIMED_OUT_REQUESTS.with_label_values(&[module_id.as_str()]).inc();
+            Err(SignerModuleError::Timeout)
+        }),
+        None => sign.await,
+    }
+}
+
+/// Signs a validator registration with the builder domain. This is kept apart
+/// from request_signature so that only the configured modules can sign
+/// registrations
+async fn handle_sign_registration(
+    Extension(req_id): Extension<RequestId>,
+    Extension(module_id): Extension<ModuleId>,
+    State(state): State<SigningState>,
+    headers: HeaderMap,
+    Json(message): Json<ValidatorRegistrationMessage>,
+) -> Result<impl IntoResponse, SignerModuleError> {
+    debug!(event = "sign_validator_registration", ?module_id, %req_id, "New request");
+
+    let allowed = state
+        .config
+        .read()
+        .await
+        .registration_modules
+        .as_ref()
+        .is_some_and(|modules| modules.contains(&module_id));
+    if !allowed {
+        warn!(?module_id, %req_id, "Module is not allowed to sign validator registrations");
+        return Err(SignerModuleError::Unauthorized);
+    }
+
+    check_nonce(&state, &module_id, &req_id, &headers).await?;
+
+    let sign = async {
+        let signing_manager = state.manager.read().await;
+        let status = signing_manager.check_key(&module_id, message.pubkey.into());
+        if status.kind == Some(KeyKind::Consensus) {
+            consume_quota(&state, &module_id, &req_id)?;
+        }
+        signing_manager.sign_validator_registration(&message).await
+    };
+    let signature = with_sign_timeout(&state, &module_id, &req_id, sign)
+        .await
+        .inspect_err(|err| record_module_error(&module_id, err))?;
+
+    state.usage.record(message.pubkey.into(), SignOperation::ValidatorRegistration);
+    state.stats.record_signature();
+
+    info!(
+        ?module_id,
+        %req_id,
+        pubkey = %message.pubkey,
+        fee_recipient = %message.fee_recipient,
+        gas_limit = message.gas_limit,
+        "Signed validator registration"
+    );
+
+    Ok((StatusCode::OK, Json(signature)).into_response())
+}
+
+/// Generates a proof of possession, only for the configured modules since it
+/// uses a different domain than request_signature
+async fn handle_proof_of_possession(
+    Extension(req_id): Extension<RequestId>,
+    Extension(module_id): Extension<ModuleId>,
+    State(state): State<SigningState>,
+    headers: HeaderMap,
+    Json(request): Json<ProofOfPossessionRequest>,
+) -> Result<impl IntoResponse, SignerModuleError> {
+    debug!(event = "proof_of_possession", ?module_id, %req_id, "New request");
+
+    let allowed = state
+        .config
+        .read()
+        .await
+        .pop_modules
+        .as_ref()
+        .is_some_and(|modules| modules.contains(&module_id));
+    if !allowed {
+        warn!(?module_id, %req_id, "Module is not allowed to request proofs of possession");
+        return Err(SignerModuleError::Unauthorized);
+    }
+
+    check_nonce(&state, &module_id, &req_id, &headers).await?;
+
+    let sign = async {
+        let signing_manager = state.manager.read().await;
+        if signing_manager.check_key(&module_id, request.pubkey.into()).available {
+            consume_quota(&state, &module_id, &req_id)?;
+        }
+        signing_manager.proof_of_possession(&module_id, &request.pubkey).await
+    };
+    let signature = with_sign_timeout(&state, &module_id, &req_id, sign)
+        .await
+        .inspect_err(|err| record_module_error(&module_id, err))?;
+
+    state.usage.record(request.pubkey.into(), SignOperation::ProofOfPossession);
+    state.stats.record_signature();
+
+    info!(?module_id, %req_id, pubkey = %request.pubkey, "Generated proof of possession");
+
+    Ok((StatusCode::OK, Json(signature)).into_response())
+}
+
+/// Records sign requests that are dropped before completing
+struct InFlightRequest<'a> {
+    module_id: &'a ModuleId,
+    req_id: &'a RequestId,
+    completed: bool,
+}
+
+impl<'a> InFlightRequest<'a> {
+    fn new(module_id: &'a ModuleId, req_id: &'a RequestId) -> Self {
+        Self { module_id, req_id, completed: false }
+    }
+
+    fn complete(mut self) {
+        self.completed = true;
+    }
+}
+
+impl Drop for InFlightRequest<'_> {
+    fn drop(&mut self) {
+        if !self.completed {
+            debug!(module_id = ?self.module_id, req_id = %self.req_id, "Sign request cancelled");
+            SIGNER_CANCELLED_REQUESTS.with_label_values(&[self.module_id.as_str()]).inc();
         }
-    }?;
+    }
+}
 
-    Ok(signature_response)
+/// Counts the errors that usually mean a module is misconfigured or a key was
+/// removed
+fn record_module_error(module_id: &ModuleId, err: &SignerModuleError) {
+    match err {
+        SignerModuleError::UnknownConsensusSigner(_) => {
+            SIGNER_UNKNOWN_CONSENSUS_SIGNER.with_label_values(&[module_id.as_str()]).inc()
+        }
+        SignerModuleError::UnknownProxySigner(_) => {
+            SIGNER_UNKNOWN_PROXY_SIGNER.with_label_values(&[module_id.as_str()]).inc()
+        }
+        _ => {}
+    }
+}
+
+/// Signatures are fixed size, so their SSZ encoding is just the raw bytes
+fn signature_response<T: Serialize + AsRef<[u8]>>(
+    signature: T,
+    is_ssz: bool,
+    signing_root: Option<[u8; 32]>,
+) -> Response {
+    if is_ssz {
+        ([(CONTENT_TYPE, SSZ_CONTENT_TYPE)], signature.as_ref().to_vec()).into_response()
+    } else if let Some(signing_root) = signing_root {
+        Json(SignatureWithRoot { signature, signing_root: signing_root.into() }).into_response()
+    } else {
+        Json(signature).into_response()
+    }
 }
 
 async fn handle_generate_proxy(
+    Extension(req_id): Extension<RequestId>,
     Extension(module_id): Extension<ModuleId>,
     State(state): State<SigningState>,
     Json(request): Json<GenerateProxyRequest>,
 ) -> Result<impl IntoResponse, SignerModuleError> {
-    let req_id = Uuid::new_v4();
-
-    debug!(event = "generate_proxy", module_id=?module_id, ?req_id, "New request");
+    debug!(event = "generate_proxy", module_id=?module_id, %req_id, "New request");
 
     let mut signing_manager = state.manager.write().await;
 
+    if !request.ephemeral && !signing_manager.has_proxy_store() {
+        warn!(?module_id, %req_id, "Rejected proxy generation, no proxy store configured");
+        return Err(SignerModuleError::MissingProxyStore);
+    }
+
     let response = match request.scheme {
         EncryptionScheme::Bls => {
-            let proxy_delegation =
-                signing_manager.create_proxy_bls(module_id, request.consensus_pubkey).await?;
-            Json(proxy_delegation).into_response()
+            let delegation = signing_manager
+                .create_versioned_proxy_bls(
+                    module_id.clone(),
+                    request.consensus_pubkey,
+                    request.version,
+                )
+                .await
+                .inspect_err(|err| record_module_error(&module_id, err))?;
+            Json(GenerateProxyResponse {
+                proxy: delegation.message.proxy,
+                scheme: request.scheme,
+                module_id,
+                delegation,
+            })
+            .into_response()
         }
         EncryptionScheme::Ecdsa => {
-            let proxy_delegation =
-                signing_manager.create_proxy_ecdsa(module_id, request.consensus_pubkey).await?;
-            Json(proxy_delegation).into_response()
+            let delegation = signing_manager
+                .create_versioned_proxy_ecdsa(
+                    module_id.clone(),
+                    request.consensus_pubkey,
+                    request.version,
+                )
+                .await
+                .inspect_err(|err| record_module_error(&module_id, err))?;
+            Json(GenerateProxyResponse {
+                proxy: delegation.message.proxy,
+                scheme: request.scheme,
+                module_id,
+                delegation,
+            })
+            .into_response()
         }
     };
 
+    state.stats.record_proxy_generation();
+
     Ok(response)
 }
+
+/// Deletes proxy keys of the module, the response follows the keymanager API
+/// delete response
+async fn handle_delete_proxy_keys(
+    Extension(req_id): Extension<RequestId>,
+    Extension(module_id): Extension<ModuleId>,
+    State(state): State<SigningState>,
+    Json(request): Json<DeleteProxyKeysRequest>,
+) -> Result<impl IntoResponse, SignerModuleError> {
+    debug!(event = "delete_proxy_keys", ?module_id, %req_id, "New request");
+
+    let mut signing_manager = state.manager.write().await;
+
+    let mut statuses = Vec::with_capacity(request.pubkeys.len());
+    let mut deleted_bls = Vec::new();
+
+    for pubkey in request.pubkeys {
+        let result = match pubkey {
+            ProxyPublicKey::Bls(pubkey) => signing_manager
+                .delete_proxy_bls(&module_id, &pubkey)
+                .inspect(|_| deleted_bls.push(pubkey)),
+            ProxyPublicKey::Ecdsa(pubkey) => {
+                signing_manager.delete_proxy_ecdsa(&module_id, &pubkey)
+            }
+        };
+
+        let status = match result {
+            Ok(()) => DeleteProxyKeyResult { status: DeleteProxyKeyStatus::Deleted, message: None },
+            Err(SignerModuleError::UnknownProxySigner(_)) => {
+                DeleteProxyKeyResult { status: DeleteProxyKeyStatus::NotFound, message: None }
+            }
+            Err(err) => {
+                error!(?module_id, %req_id, ?pubkey, %err, "Failed to delete proxy key");
+                DeleteProxyKeyResult {
+                    status: DeleteProxyKeyStatus::Error,
+                    message: Some(err.to_string()),
+                }
+            }
+        };
+        statuses.push(status);
+    }
+
+    let res = DeleteProxyKeysResponse::new(statuses, &deleted_bls)
+        .map_err(|err| SignerModuleError::Internal(err.to_string()))?;
+
+    Ok((StatusCode::OK, Json(res)).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::to_bytes, http::header::AUTHORIZATION};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    const MODULE_JWT: &str = "test-module-jwt";
+    const ADMIN_JWT: &str = "test-admin-jwt";
+
+    fn module_id() -> ModuleId {
+        ModuleId("TEST_MODULE".to_string())
+    }
+
+    /// State of a signer with one module and one consensus key, without proxy
+    /// store or any limit
+    fn test_state() -> (SigningState, BlsPublicKey) {
+        let chain = Chain::Holesky;
+        let mut manager = SigningManager::new(chain, None).unwrap();
+        let consensus_signer = ConsensusSigner::new_random();
+        let consensus_pk = consensus_signer.pubkey();
+        manager.add_consensus_signer(consensus_signer);
+
+        let config = EffectiveConfig {
+            chain,
+            binds: vec![],
+            loader: SignerLoader::File { key_path: PathBuf::new() },
+            store: None,
+            store_failure: StoreFailurePolicy::Fail,
+            consensus_allowlist: None,
+            signature_quota: None,
+            withdrawal_check: None,
+            max_consensus_keys: None,
+            allow_empty_signer: false,
+            verify_signatures: false,
+            sign_timeout_ms: None,
+            module_denylist: None,
+            registration_modules: None,
+            pop_modules: None,
+            status_endpoint: StatusEndpoint::Public,
+            prune_passwords_interval_secs: None,
+            max_proxies_per_key: None,
+            proxy_selection: ProxySelection::MostRecent,
+            orphan_proxies: OrphanProxyPolicy::Fail,
+            min_jwt_length: 0,
+            strict_jwts: false,
+            jwts_file: None,
+            persisted_stats: None,
+            nonce_file: None,
+            nonce_window: 0,
+        };
+        let state = SigningState {
+            manager: Arc::new(RwLock::new(manager)),
+            jwts: Arc::new(RwLock::new(BiHashMap::from_iter([(
+                module_id(),
+                Jwt(MODULE_JWT.to_string()),
+            )]))),
+            denylist: Arc::new(RwLock::new(ModuleDenylist::default())),
+            quotas: None,
+            sign_timeout: None,
+            chain,
+            usage: Arc::new(KeyUsage::default()),
+            stats: Arc::new(SignerStats::default()),
+            nonces: None,
+            admin_jwt: None,
+            config: Arc::new(RwLock::new(config)),
+        };
+
+        (state, consensus_pk)
+    }
+
+    fn request(method: Method, path: &str, jwt: &str) -> axum::http::request::Builder {
+        axum::http::Request::builder()
+            .method(method)
+            .uri(path)
+            .header(AUTHORIZATION, format!("Bearer {jwt}"))
+    }
+
+    fn sign_request(pubkey: BlsPublicKey) -> axum::http::Request<Body> {
+        let body = SignRequest::Consensus(SignConsensusRequest::new(pubkey, [1; 32]));
+        request(Method::POST, REQUEST_SIGNATURE_PATH, MODULE_JWT)
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(serde_json::to_vec(&body).unwrap()))
+            .unwrap()
+    }
+
+    async fn body_bytes(response: Response) -> Bytes {
+        to_bytes(response.into_body(), usize::MAX).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_request_id_is_echoed() {
+        let (state, _) = test_state();
+        let router = build_router(state, StatusEndpoint::Public);
+
+        let req = request(Method::GET, PERMISSIONS_PATH, MODULE_JWT)
+            .header(REQUEST_ID_HEADER, "module-request-1")
+            .body(Body::empty())
+            .unwrap();
+        let response = router.clone().oneshot(req).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[REQUEST_ID_HEADER], "module-request-1");
+
+        // generated if missing or too long
+        for req_id in [None, Some("a".repeat(MAX_REQUEST_ID_LEN + 1))] {
+            let mut req = request(Method::GET, PERMISSIONS_PATH, MODULE_JWT);
+            if let Some(req_id) = req_id {
+                req = req.header(REQUEST_ID_HEADER, req_id);
+            }
+            let response = router.clone().oneshot(req.body(Body::empty()).unwrap()).await.unwrap();
+            let echoed = response.headers()[REQUEST_ID_HEADER].to_str().unwrap();
+            assert!(Uuid::parse_str(echoed).is_ok());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_serves_unix_socket() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let tmp_path = std::env::temp_dir().join("test_serves_unix_socket");
+        let _ = std::fs::remove_dir_all(&tmp_path);
+        std::fs::create_dir_all(&tmp_path).unwrap();
+        let path = tmp_path.join("signer.sock");
+
+        // anything but a socket is never replaced
+        std::fs::write(&path, "").unwrap();
+        assert!(bind_unix_socket(&path, 0o600).is_err());
+        std::fs::remove_file(&path).unwrap();
+
+        // a socket left by a previous run is
+        drop(bind_unix_socket(&path, 0o600).unwrap());
+        let listener = bind_unix_socket(&path, 0o660).unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o660);
+
+        let (state, _) = test_state();
+        tokio::spawn(
+            axum::serve(listener, build_router(state, StatusEndpoint::Public)).into_future(),
+        );
+
+        let mut stream = tokio::net::UnixStream::connect(&path).await.unwrap();
+        let req = format!(
+            "GET {PERMISSIONS_PATH} HTTP/1.1\r\nHost: localhost\r\nAuthorization: Bearer {MODULE_JWT}\r\nConnection: close\r\n\r\n"
+        );
+        stream.write_all(req.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+        assert!(response.contains("TEST_MODULE"));
+    }
+
+    #[tokio::test]
+    async fn test_get_pubkeys_ndjson_orphans_last() {
+        let (state, _) = test_state();
+        state.config.write().await.orphan_proxies = OrphanProxyPolicy::Report;
+        let consensus = ConsensusSigner::new_random();
+        let consensus_pk = consensus.pubkey();
+        {
+            let mut manager = state.manager.write().await;
+            // a proxy whose consensus key is removed afterwards
+            let removed = ConsensusSigner::new_random();
+            manager.replace_consensus_signers(vec![removed.clone()]);
+            manager.create_proxy_ecdsa(module_id(), removed.pubkey()).await.unwrap();
+            manager.replace_consensus_signers(vec![consensus]);
+            manager.create_proxy_bls(module_id(), consensus_pk).await.unwrap();
+        }
+        let router = build_router(state, StatusEndpoint::Public);
+
+        let req = request(Method::GET, GET_PUBKEYS_PATH, MODULE_JWT)
+            .header(ACCEPT, NDJSON_CONTENT_TYPE)
+            .body(Body::empty())
+            .unwrap();
+        let response = router.oneshot(req).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CONTENT_TYPE], NDJSON_CONTENT_TYPE);
+
+        let body = body_bytes(response).await;
+        let lines: Vec<serde_json::Value> = std::str::from_utf8(&body)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["consensus"], consensus_pk.to_string());
+        assert_eq!(lines[0]["proxy_bls"].as_array().unwrap().len(), 1);
+        assert_eq!(lines[1]["orphans"]["proxy_ecdsa"].as_array().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_route_returns_json_error() {
+        let (state, _) = test_state();
+        let router = build_router(state, StatusEndpoint::Public);
+
+        let req =
+            request(Method::GET, "/signer/v1/unknown", MODULE_JWT).body(Body::empty()).unwrap();
+        let response = router.oneshot(req).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let body: serde_json::Value = serde_json::from_slice(&body_bytes(response).await).unwrap();
+        assert_eq!(body["code"], 404);
+        assert_eq!(body["message"], "unknown route: /signer/v1/unknown");
+    }
+
+    #[tokio::test]
+    async fn test_status_endpoint_modes() {
+        let status = |jwt: Option<&str>| {
+            let mut req = axum::http::Request::builder().uri(STATUS_PATH);
+            if let Some(jwt) = jwt {
+                req = req.header(AUTHORIZATION, format!("Bearer {jwt}"));
+            }
+            req.body(Body::empty()).unwrap()
+        };
+        let cases = [
+            (StatusEndpoint::Public, None, StatusCode::OK),
+            (StatusEndpoint::Authenticated, None, StatusCode::BAD_REQUEST),
+            (StatusEndpoint::Authenticated, Some("wrong-jwt"), StatusCode::UNAUTHORIZED),
+            (StatusEndpoint::Authenticated, Some(MODULE_JWT), StatusCode::OK),
+            (StatusEndpoint::Disabled, Some(MODULE_JWT), StatusCode::NOT_FOUND),
+        ];
+
+        for (endpoint, jwt, expected) in cases {
+            let (state, _) = test_state();
+            let response = build_router(state, endpoint).oneshot(status(jwt)).await.unwrap();
+            assert_eq!(response.status(), expected, "{endpoint:?} with {jwt:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_admin_endpoints_require_admin_jwt() {
+        let config =
+            |jwt| request(Method::GET, SIGNER_CONFIG_PATH, jwt).body(Body::empty()).unwrap();
+
+        // not served without an admin JWT
+        let (state, _) = test_state();
+        let response = build_router(state, StatusEndpoint::Public).oneshot(config(ADMIN_JWT)).await;
+        assert_eq!(response.unwrap().status(), StatusCode::NOT_FOUND);
+
+        let (mut state, _) = test_state();
+        state.admin_jwt = Some(Jwt(ADMIN_JWT.to_string()));
+        let router = build_router(state, StatusEndpoint::Public);
+
+        for jwt in [MODULE_JWT, "test-admin-jw", "test-admin-jwtt"] {
+            let response = router.clone().oneshot(config(jwt)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED, "{jwt}");
+        }
+
+        let response = router.oneshot(config(ADMIN_JWT)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = body_bytes(response).await;
+        let body = std::str::from_utf8(&body).unwrap();
+        assert!(body.contains("TEST_MODULE"));
+        assert!(!body.contains(MODULE_JWT) && !body.contains(ADMIN_JWT));
+    }
+
+    #[tokio::test]
+    async fn test_sign_timeout() {
+        let (mut state, consensus_pk) = test_state();
+        state.sign_timeout = Some(Duration::from_millis(50));
+        let router = build_router(state.clone(), StatusEndpoint::Public);
+        let timed_out =
+            || SIGNER_TIMED_OUT_REQUESTS.with_label_values(&[module_id().as_str()]).get();
+        let before = timed_out();
+
+        // the signer is busy until the lock is released
+        let manager = state.manager.write().await;
+        let response = router.clone().oneshot(sign_request(consensus_pk)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+        assert_eq!(timed_out(), before + 1);
+        drop(manager);
+
+        let response = router.oneshot(sign_request(consensus_pk)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_sign_request_is_counted() {
+        let (state, consensus_pk) = test_state();
+        let router = build_router(state.clone(), StatusEndpoint::Public);
+        let cancelled =
+            || SIGNER_CANCELLED_REQUESTS.with_label_values(&[module_id().as_str()]).get();
+        let before = cancelled();
+
+        // the module gives up while the signer is busy, dropping the request
+        let manager = state.manager.write().await;
+        let sign = router.oneshot(sign_request(consensus_pk));
+        assert!(tokio::time::timeout(Duration::from_millis(50), sign).await.is_err());
+        drop(manager);
+
+        assert_eq!(cancelled(), before + 1);
+    }
+
+    #[tokio::test]
+    async fn test_generate_without_store_requires_ephemeral() {
+        let (state, consensus_pk) = test_state();
+        let router = build_router(state.clone(), StatusEndpoint::Public);
+        let generate = |ephemeral| {
+            let body = GenerateProxyRequest::new(consensus_pk, EncryptionScheme::Ecdsa)
+                .with_ephemeral(ephemeral);
+            request(Method::POST, GENERATE_PROXY_KEY_PATH, MODULE_JWT)
+                .header(CONTENT_TYPE, "application/json")
+                .body(Body::from(serde_json::to_vec(&body).unwrap()))
+                .unwrap()
+        };
+
+        let response = router.clone().oneshot(generate(false)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert!(state.manager.read().await.proxies().ecdsa_signers.is_empty());
+
+        let response = router.oneshot(generate(true)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(state.manager.read().await.proxies().ecdsa_signers.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_json_logs_include_request_fields() {
+        use std::sync::Mutex;
+
+        use cb_common::{config::LogFormat, utils::stdout_layer};
+        use tracing_subscriber::layer::SubscriberExt;
+
+        #[derive(Clone, Default)]
+        struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+        impl std::io::Write for LogBuffer {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let logs = LogBuffer::default();
+        let writer = logs.clone();
+        let layer = stdout_layer(LogFormat::Json, move || writer.clone());
+        let _guard = tracing::subscriber::set_default(tracing_subscriber::registry().with(layer));
+
+        let (state, _) = test_state();
+        let req = request(Method::GET, PERMISSIONS_PATH, MODULE_JWT)
+            .header(REQUEST_ID_HEADER, "json-logs-request")
+            .body(Body::empty())
+            .unwrap();
+        let response = build_router(state, StatusEndpoint::Public).oneshot(req).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        let completed = logs
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .find(|log| log["fields"]["message"] == "Request completed")
+            .unwrap();
+        let span = &completed["spans"][0];
+        assert_eq!(span["req_id"], "json-logs-request");
+        assert_eq!(span["module_id"], "TEST_MODULE");
+        assert_eq!(span["status"], 200);
+    }
+}
@@ -0,0 +1,122 @@
+use std::{
+    path::Path,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use cb_common::utils::utcnow_sec;
+use eyre::Context;
+use serde::{Deserialize, Serialize};
+
+/// Cumulative totals of the signer. Unlike the Prometheus metrics these can be
+/// persisted, so the totals survive restarts
+pub struct SignerStats {
+    signatures: AtomicU64,
+    proxy_generations: AtomicU64,
+    key_unlocks: AtomicU64,
+    /// Unix timestamp in seconds of when the totals started counting
+    since: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatsSnapshot {
+    pub signatures: u64,
+    pub proxy_generations: u64,
+    /// Consensus keys decrypted at startup and on reload
+    pub key_unlocks: u64,
+    pub since: u64,
+}
+
+impl Default for SignerStats {
+    fn default() -> Self {
+        Self::restore(StatsSnapshot {
+            signatures: 0,
+            proxy_generations: 0,
+            key_unlocks: 0,
+            since: utcnow_sec(),
+        })
+    }
+}
+
+impl SignerStats {
+    fn restore(snapshot: StatsSnapshot) -> Self {
+        Self {
+            signatures: AtomicU64::new(snapshot.signatures),
+            proxy_generations: AtomicU64::new(snapshot.proxy_generations),
+            key_unlocks: AtomicU64::new(snapshot.key_unlocks),
+            since: snapshot.since,
+        }
+    }
+
+    /// Restores the totals saved in the file, starting from zero if it doesn't
+    /// exist yet
+    pub fn load(path: &Path) -> eyre::Result<Self> {
+        match std::fs::read(path) {
+            Ok(bytes) => {
+                let snapshot: StatsSnapshot = serde_json::from_slice(&bytes)
+                    .wrap_err_with(|| format!("invalid stats file {path:?}"))?;
+                Ok(Self::restore(snapshot))
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err).wrap_err_with(|| format!("failed to read stats file {path:?}")),
+        }
+    }
+
+    /// Saves the totals, replacing the file atomically so a crash never leaves
+    /// it half written
+    pub fn persist(&self, path: &Path) -> eyre::Result<()> {
+        let mut tmp_path = path.as_os_str().to_owned();
+        tmp_path.push(".tmp");
+
+        std::fs::write(&tmp_path, serde_json::to_vec(&self.snapshot())?)?;
+        std::fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+
+    pub fn record_signature(&self) {
+        self.signatures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_proxy_generation(&self) {
+        self.proxy_generations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_key_unlocks(&self, count: usize) {
+        self.key_unlocks.fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> StatsSnapshot {
+        StatsSnapshot {
+            signatures: self.signatures.load(Ordering::Relaxed),
+            proxy_generations: self.proxy_generations.load(Ordering::Relaxed),
+            key_unlocks: self.key_unlocks.load(Ordering::Relaxed),
+            since: self.since,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_survive_restart() {
+        let tmp_path = std::env::temp_dir().join("test_stats_survive_restart");
+        let _ = std::fs::remove_dir_all(&tmp_path);
+        std::fs::create_dir_all(&tmp_path).unwrap();
+        let path = tmp_path.join("stats.json");
+
+        let stats = SignerStats::load(&path).unwrap();
+        stats.record_signature();
+        stats.record_signature();
+        stats.record_proxy_generation();
+        stats.record_key_unlocks(3);
+        stats.persist(&path).unwrap();
+
+        let restored = SignerStats::load(&path).unwrap();
+        assert_eq!(restored.snapshot(), stats.snapshot());
+
+        restored.record_signature();
+        assert_eq!(restored.snapshot().signatures, 3);
+        assert!(!tmp_path.join("stats.json.tmp").exists());
+    }
+}
@@ -0,0 +1,100 @@
+use std::{cmp::Reverse, collections::HashMap, sync::Mutex};
+
+use cb_common::{commit::request::ProxyPublicKey, utils::utcnow_sec};
+use serde::Serialize;
+
+/// Kind of signature a key produced
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignOperation {
+    Consensus,
+    ProxyBls,
+    ProxyEcdsa,
+    ValidatorRegistration,
+    ProofOfPossession,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyUsageEntry {
+    pub pubkey: ProxyPublicKey,
+    pub operation: SignOperation,
+    /// Unix timestamp in seconds
+    pub last_signed_at: u64,
+}
+
+/// Tracks the last signature of each key, only in memory so it's reset on
+/// restart
+#[derive(Default)]
+pub struct KeyUsage {
+    keys: Mutex<HashMap<ProxyPublicKey, KeyUsageEntry>>,
+}
+
+impl KeyUsage {
+    pub fn record(&self, pubkey: ProxyPublicKey, operation: SignOperation) {
+        self.record_at(pubkey, operation, utcnow_sec())
+    }
+
+    fn record_at(&self, pubkey: ProxyPublicKey, operation: SignOperation, now: u64) {
+        let entry = KeyUsageEntry { pubkey, operation, last_signed_at: now };
+        self.keys.lock().expect("usage lock poisoned").insert(pubkey, entry);
+    }
+
+    /// Sorts the keys by last signature, most recent first. Keys that never
+    /// signed go last, in their original order
+    pub fn sort_most_recent_first<T: Copy + Into<ProxyPublicKey>>(&self, keys: &mut [T]) {
+        let usage = self.keys.lock().expect("usage lock poisoned");
+        keys.sort_by_cached_key(|key| {
+            Reverse(usage.get(&(*key).into()).map(|entry| entry.last_signed_at))
+        });
+    }
+
+    /// Returns the usage of every key that signed at least once, most recent
+    /// first
+    pub fn entries(&self) -> Vec<KeyUsageEntry> {
+        let mut entries: Vec<KeyUsageEntry> =
+            self.keys.lock().expect("usage lock poisoned").values().cloned().collect();
+        entries.sort_by(|a, b| b.last_signed_at.cmp(&a.last_signed_at));
+        entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::rpc::types::beacon::BlsPublicKey as BlsPublicKeyInner;
+    use cb_common::signer::{BlsPublicKey, EcdsaPublicKey};
+
+    use super::*;
+
+    #[test]
+    fn test_usage_keeps_last_signature() {
+        let usage = KeyUsage::default();
+        let bls: ProxyPublicKey = BlsPublicKey::from(BlsPublicKeyInner::repeat_byte(1)).into();
+        let ecdsa: ProxyPublicKey = EcdsaPublicKey::from([2; 33]).into();
+
+        usage.record_at(bls, SignOperation::Consensus, 10);
+        usage.record_at(ecdsa, SignOperation::ProxyEcdsa, 20);
+        usage.record_at(bls, SignOperation::ValidatorRegistration, 30);
+
+        let entries = usage.entries();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].pubkey, bls);
+        assert_eq!(entries[0].operation, SignOperation::ValidatorRegistration);
+        assert_eq!(entries[0].last_signed_at, 30);
+        assert_eq!(entries[1].pubkey, ecdsa);
+        assert_eq!(entries[1].last_signed_at, 20);
+    }
+
+    #[test]
+    fn test_sort_most_recent_first() {
+        let usage = KeyUsage::default();
+        let keys: Vec<BlsPublicKey> =
+            (1..=4).map(|i| BlsPublicKey::from(BlsPublicKeyInner::repeat_byte(i))).collect();
+
+        usage.record_at(keys[1].into(), SignOperation::ProxyBls, 10);
+        usage.record_at(keys[3].into(), SignOperation::ProxyBls, 20);
+
+        let mut sorted = keys.clone();
+        usage.sort_most_recent_first(&mut sorted);
+        assert_eq!(sorted, vec![keys[3], keys[1], keys[0], keys[2]]);
+    }
+}